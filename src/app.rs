@@ -1,7 +1,14 @@
-use crate::{export_json::{FocusTime, MeetingList, Worktime}, read_json::read_json};
-use anyhow::Result;
-use chrono::Local;
+use crate::model::{paused_minutes, ActionItem, BreakTime, FocusTime, MeetingList, Pause, ProjectTime, Timestamp, Worktime};
+use crate::calc_time::{calc_endtime, local_timestamp, minutes_between, next_after, now, parse_time, parse_today};
+use crate::calendar::{Calendar, CalendarEvent};
+use crate::config::Config;
+use crate::journal::{Journal, JournalEntry, JournalEvent};
+use anyhow::{anyhow, Result};
+use crate::export_csv::{default_export_path, export_csv, DEFAULT_EXPORT_DIR};
+use crate::export_notes::write_notes;
+use chrono::{Datelike, Duration, Local, NaiveDate};
 use std::collections::HashMap;
+use std::time::Instant;
 use std::io::prelude::*;
 use ratatui::{
     buffer::Buffer,
@@ -11,14 +18,27 @@ use ratatui::{
         palette::tailwind, Color,
     },
 };
-use crate::tabs::{BarChartTab, EditHistoryTab, FocusTimeTab, MeetingNotesTab};
+use crate::store::open_default;
+use crate::tabs::{BalanceTab, BarChartTab, EditHistoryTab, FocusTimeTab, MeetingNotesTab, NoteEditor, NoteEntry, NoteTarget, NotesView, ReportTab};
+use crossterm::event::KeyCode;
 use strum::{Display, EnumIter, FromRepr, IntoEnumIterator};
 
+// How far ahead of now a typed start may lie before it is taken for yesterday's
+const FUTURE_START_HOURS: i64 = 4;
+
+// How long typing has to pause before the notes are saved
+const NOTES_AUTOSAVE_DELAY: std::time::Duration = std::time::Duration::from_secs(3);
+
 pub enum CurrentScreen {
     Main,
     EditingStarttime,
     EditingEndtime,
     EditingMeetingName,
+    EditingProject,
+    EditingNotes,
+    AddingActionItem,
+    SearchingActionItems,
+    ExportingCsv,
     Exiting,
 }
 
@@ -26,6 +46,11 @@ pub enum CurrentlyEditing {
     Starttime,
     Endtime,
     MeetingName,
+    Project,
+    Notes,
+    ActionItem,
+    ActionItemSearch,
+    ExportRange,
 }
 
 #[derive(Debug, Clone, Copy, Default, Display, EnumIter, FromRepr, PartialEq, Eq)]
@@ -35,6 +60,8 @@ pub enum Tab {
     EditHistoryTab,
     FocusTimeTab,
     MeetingNotesTab,
+    BalanceTab,
+    ReportTab,
 }
 
 impl Tab {
@@ -63,6 +90,8 @@ impl Tab {
             Self::EditHistoryTab => tailwind::EMERALD,
             Self::FocusTimeTab => tailwind::BLUE,
             Self::MeetingNotesTab => tailwind::GREEN,
+            Self::BalanceTab => tailwind::AMBER,
+            Self::ReportTab => tailwind::SKY,
         }
     }  
 }
@@ -70,19 +99,28 @@ impl Tab {
 pub struct App {
     pub starttime_key: String,   // the currently being edited json key.
     pub starttime_input: String, // the currently being edited json value.
-    pub starttime_pairs: HashMap<String, String>, // The representation of our key and value pairs with serde Serialize support
+    pub starttime_pairs: HashMap<String, Timestamp>, // The representation of our key and value pairs with serde Serialize support
     pub endtime_key: String,                      // the currently being edited json key.
     pub endtime_input: String,                    // the currently being edited json value.
-    pub endtime_pairs: HashMap<String, String>, // The representation of our key and value pairs with serde Serialize support
+    pub endtime_pairs: HashMap<String, Timestamp>, // The representation of our key and value pairs with serde Serialize support
     pub current_screen: CurrentScreen, // the current screen the user is looking at, and will later determine what is rendered.
     pub currently_editing: Option<CurrentlyEditing>, // the optional state containing which of the key or value pair the user is editing. It is an option, because when the user is not directly editing a key-value pair, this will be set to `None`.
     pub time_in_meetings: i32,
     pub meeting_running: bool,
-    pub meeting_start_time: String,
-    pub meeting_end_time: String,
+    pub meeting_start_time: Timestamp,
+    pub meeting_end_time: Timestamp,
     pub meeting_list: Vec<MeetingList>,
     pub meeting_name_input: String,
     pub meeting_name: String,
+    pub meeting_suggestions: Vec<CalendarEvent>,
+    pub meeting_project: Option<String>,
+    pub meeting_billable: bool,
+    pub meeting_notes: String,
+    pub meeting_action_items: Vec<ActionItem>,
+    pub meeting_paused: bool,
+    pub meeting_pause_start: Timestamp,
+    pub meeting_pauses: Vec<Pause>,
+    pub action_item_input: String,
     pub do_print: bool,
     pub should_exit: bool,
     pub default_starttime: String,
@@ -95,14 +133,36 @@ pub struct App {
     pub focus: bool,
     pub focus_time: u64,
     pub focus_time_list: Vec<FocusTime>,
-    pub focus_time_start: String,
-    pub focus_time_end: String,
+    pub focus_time_start: Timestamp,
+    pub focus_time_end: Timestamp,
     pub focus_time_total: u64,
+    pub focus_project: Option<String>,
+    pub focus_billable: bool,
+    pub focus_paused: bool,
+    pub focus_pause_start: Timestamp,
+    pub focus_pauses: Vec<Pause>,
+    pub break_running: bool,
+    pub time_in_break: i32,
+    pub break_start_time: Timestamp,
+    pub break_list: Vec<BreakTime>,
+    pub project_running: bool,
+    pub project_name: String,
+    pub project_task: Option<String>,
+    pub project_start_time: Timestamp,
+    pub time_in_project: i32,
+    pub project_list: Vec<ProjectTime>,
+    pub project_input: String,
+    pub export_range_input: String,
+    pub export_message: Option<String>,
     pub tab: Tab,
     pub focus_time_tab: FocusTimeTab,
     pub meeting_notes_tab: MeetingNotesTab,
     pub edit_history_tab: EditHistoryTab,
     pub barchart_tab: BarChartTab,
+    pub balance_tab: BalanceTab,
+    pub report_tab: ReportTab,
+    pub journal: Journal,
+    pub config: Config,
 }
 
 impl Widget for &App {
@@ -117,8 +177,39 @@ impl Widget for &App {
     }
 }
 
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl App {
     pub fn new() -> App {
+        App::with_config(Config::load_default().unwrap_or_default())
+    }
+
+    pub fn with_config(config: Config) -> App {
+        let mut app = Self::headless(config);
+        app.barchart_tab = BarChartTab::new();
+        app.balance_tab = BalanceTab::with_config(&app.config);
+        app.report_tab = ReportTab::with_config(&app.config);
+        app.meeting_notes_tab = MeetingNotesTab::load();
+        app.edit_history_tab = EditHistoryTab::load();
+        app
+    }
+
+    /// Read the history again for the tabs that show it, after days were
+    /// stored outside of them.
+    pub fn reload_history(&mut self) {
+        self.edit_history_tab.reload();
+        self.barchart_tab.reload();
+        self.balance_tab = BalanceTab::with_config(&self.config);
+        self.report_tab.reload();
+    }
+
+    /// An app for the command line: the tabs stay empty, so nothing is read
+    /// from the history.
+    pub fn headless(config: Config) -> App {
         App {
             starttime_key: String::from("Starttime"),
             starttime_input: String::new(),
@@ -130,14 +221,23 @@ impl App {
             currently_editing: None,
             time_in_meetings: 0,
             meeting_running: false,
-            meeting_start_time: String::new(),
-            meeting_end_time: String::new(),
+            meeting_start_time: now(),
+            meeting_end_time: now(),
             meeting_list: Vec::new(),
             meeting_name_input: String::new(),
             meeting_name: String::new(),
+            meeting_suggestions: Vec::new(),
+            meeting_project: None,
+            meeting_billable: true,
+            meeting_notes: String::new(),
+            meeting_action_items: Vec::new(),
+            meeting_paused: false,
+            meeting_pause_start: now(),
+            meeting_pauses: Vec::new(),
+            action_item_input: String::new(),
             do_print: false,
             should_exit: false,
-            default_starttime: String::from("08:45"),
+            default_starttime: config.default_starttime.clone(),
             current_worktime: 0,
             total_time_in_meetings: 0,
             scrollbar_state: ScrollbarState::default(),
@@ -147,14 +247,36 @@ impl App {
             focus: false,
             focus_time: 0,
             focus_time_list: Vec::new(),
-            focus_time_start: String::new(),
-            focus_time_end: String::new(),
+            focus_time_start: now(),
+            focus_time_end: now(),
             focus_time_total: 0,
+            focus_project: None,
+            focus_billable: true,
+            focus_paused: false,
+            focus_pause_start: now(),
+            focus_pauses: Vec::new(),
+            break_running: false,
+            time_in_break: 0,
+            break_start_time: now(),
+            break_list: Vec::new(),
+            project_running: false,
+            project_name: String::new(),
+            project_task: None,
+            project_start_time: now(),
+            time_in_project: 0,
+            project_list: Vec::new(),
+            project_input: String::new(),
+            export_range_input: String::new(),
+            export_message: None,
             tab: Tab::BarChartTab,
             focus_time_tab: FocusTimeTab::new(),
             meeting_notes_tab: MeetingNotesTab::new(),
             edit_history_tab: EditHistoryTab::new(),
-            barchart_tab: BarChartTab::new(),
+            barchart_tab: BarChartTab::empty(),
+            balance_tab: BalanceTab::empty(),
+            report_tab: ReportTab::empty(),
+            journal: Journal::open_default(),
+            config,
         }
     }
 
@@ -181,27 +303,142 @@ impl App {
     fn render_selected_tab(&self, area: Rect, buf: &mut Buffer) {
         match self.tab {
             Tab::BarChartTab => self.barchart_tab.clone().render(area, buf),
-            Tab::EditHistoryTab => self.edit_history_tab.render(area, buf),
-            Tab::FocusTimeTab => self.focus_time_tab.render(area, buf),
-            Tab::MeetingNotesTab => self.meeting_notes_tab.clone().render(area, buf),
+            Tab::EditHistoryTab => (&self.edit_history_tab).render(area, buf),
+            Tab::FocusTimeTab => self.focus_time_tab.paused(self.focus_paused).render(area, buf),
+            Tab::MeetingNotesTab => self.meeting_notes_tab.render_entries(&self.note_entries(), area, buf),
+            Tab::BalanceTab => self.balance_tab.render(area, buf),
+            Tab::ReportTab => self.report_tab.render(area, buf),
         };
     }
 
-    pub fn save_starttime_value(&mut self) {
-        self.starttime_pairs
-            .insert(self.starttime_key.clone(), self.starttime_input.clone());
+    /// Returns false and keeps the popup open if the input is not a valid "HH:MM".
+    /// A start more than a few hours later than now belongs to a shift that
+    /// began yesterday; one just ahead of now is still today's.
+    pub fn save_starttime_value(&mut self) -> bool {
+        let Some(mut time) = parse_today(&self.starttime_input) else {
+            return false;
+        };
+        if time > now() + Duration::hours(FUTURE_START_HOURS) {
+            time -= Duration::days(1);
+        }
+        self.record(JournalEvent::StartSet { time });
+        self.starttime_pairs.insert(self.starttime_key.clone(), time);
 
         self.starttime_input = String::new();
 
         self.currently_editing = None;
+        true
     }
 
-    pub fn save_endtime_value(&mut self) {
-        self.endtime_pairs
-            .insert(self.endtime_key.clone(), self.endtime_input.clone());
+    /// Returns false and keeps the popup open if the input is not a valid "HH:MM".
+    /// The end is the first such time after the start, so a shift from 22:00
+    /// to 06:00 ends on the next day.
+    pub fn save_endtime_value(&mut self) -> bool {
+        let Some(time) = parse_time(&self.endtime_input) else {
+            return false;
+        };
+        let time = next_after(self.starttime(), time);
+        self.record(JournalEvent::EndSet { time });
+        self.endtime_pairs.insert(self.endtime_key.clone(), time);
 
         self.endtime_input = String::new();
         self.currently_editing = None;
+        true
+    }
+
+    /// The start of today's work: the entered start time, or the default one.
+    pub fn starttime(&self) -> Timestamp {
+        self.starttime_pairs
+            .get(&self.starttime_key)
+            .copied()
+            .or_else(|| parse_today(&self.default_starttime))
+            .unwrap_or_else(now)
+    }
+
+    /// Minutes the user is supposed to work on the day that is running.
+    pub fn target_minutes(&self) -> i64 {
+        let date = self.starttime().with_timezone(&Local).date_naive();
+        self.config.schedule.target_minutes(date)
+    }
+
+    /// Minutes of breaks today, the running one included.
+    pub fn break_minutes(&self) -> i64 {
+        let recorded: i64 = self.break_list.iter().map(|b| i64::from(b.time_in_break)).sum();
+        let running = if self.break_running { i64::from(self.time_in_break) } else { 0 };
+        recorded + running
+    }
+
+    /// Net minutes worked from the start until `at`: breaks and any break the
+    /// rules still require are not counted.
+    pub fn worked_minutes(&self, at: Timestamp) -> i64 {
+        let without_breaks = minutes_between(self.starttime(), at) - self.break_minutes();
+        without_breaks - self.config.breaks.missing_break(without_breaks, self.break_minutes())
+    }
+
+    /// Minutes until the break rules require another break, see
+    /// [`BreakRules::next_due`](crate::config::BreakRules::next_due).
+    pub fn break_due_in(&self) -> Option<i64> {
+        if self.break_running {
+            return None;
+        }
+        let without_breaks = minutes_between(self.starttime(), now()) - self.break_minutes();
+        self.config.breaks.next_due(without_breaks, self.break_minutes())
+    }
+
+    /// Net minutes worked today until `at`. If today was stored already, the
+    /// stored record counts, together with anything tracked since, the way
+    /// the next export merges them.
+    pub fn worked_today(&self, at: Timestamp) -> i64 {
+        let date = self.starttime().with_timezone(&Local).date_naive();
+        let Some(stored) = self
+            .balance_tab
+            .stored_today()
+            .filter(|d| d.date == date.format("%Y-%m-%d").to_string())
+        else {
+            return self.worked_minutes(at).max(0);
+        };
+        let mut day = stored.clone();
+        if self.is_tracking() {
+            let mut tracked = self.worktime(date, at);
+            if self.break_running {
+                tracked.breaks.push(BreakTime {
+                    break_start_time: self.break_start_time,
+                    break_end_time: at,
+                    time_in_break: minutes_between(self.break_start_time, at) as i32,
+                });
+            }
+            day.merge(tracked);
+        }
+        day.worked_minutes(&self.config.breaks).max(0)
+    }
+
+    // Whether a start or any session was recorded for the running day.
+    fn is_tracking(&self) -> bool {
+        self.starttime_pairs.contains_key(&self.starttime_key)
+            || self.meeting_running
+            || self.focus
+            || self.break_running
+            || self.project_running
+            || !self.meeting_list.is_empty()
+            || !self.focus_time_list.is_empty()
+            || !self.break_list.is_empty()
+            || !self.project_list.is_empty()
+    }
+
+    /// Today's contribution to the overtime balance until `at`.
+    pub fn balance_today(&self, at: Timestamp) -> i64 {
+        self.worked_today(at) - self.target_minutes()
+    }
+
+    /// Overtime balance including today; the history's part ends yesterday.
+    pub fn balance_total(&self, at: Timestamp) -> i64 {
+        self.balance_tab.balance().total() + self.balance_today(at)
+    }
+
+    /// When today's target is reached, counted net of breaks like
+    /// [`Self::worked_minutes`].
+    pub fn predicted_endtime(&self) -> Timestamp {
+        calc_endtime(self.starttime(), &self.config.schedule, &self.config.breaks, self.break_minutes())
     }
 
     pub fn print_json(&self) -> Result<()> {
@@ -212,30 +449,149 @@ impl App {
         Ok(())
     }
 
+    /// Start a meeting named by `meeting_name_input`, booked to the running
+    /// project.
     pub fn start_meeting(&mut self) {
+        self.start_meeting_for(self.running_project());
+    }
+
+    pub fn start_meeting_for(&mut self, project: Option<String>) {
+        self.start_meeting_with(project, true);
+    }
+
+    pub fn start_meeting_with(&mut self, project: Option<String>, billable: bool) {
         self.meeting_running = true;
         self.meeting_name = self.meeting_name_input.clone();
         self.meeting_name_input = String::new();
-        self.meeting_start_time = Local::now().format("%H:%M").to_string();
+        self.meeting_start_time = now();
+        self.meeting_project = project;
+        self.meeting_billable = billable;
+        self.meeting_notes = String::new();
+        self.meeting_action_items = Vec::new();
+        self.meeting_paused = false;
+        self.meeting_pauses = Vec::new();
+        self.meeting_notes_tab.selected = 0;
+        self.record(JournalEvent::MeetingStarted {
+            name: self.meeting_name.clone(),
+            time: self.meeting_start_time,
+            project: self.meeting_project.clone(),
+            billable,
+        });
         self.currently_editing = None;
         self.tab = Tab::MeetingNotesTab;
         //self.time_in_meetings = meeting_timer(self.meeting_running);
     }
 
     pub fn end_meeting(&mut self) {
+        // notes still being typed go with the meeting
+        self.save_notes();
         self.meeting_running = false;
-        self.meeting_end_time = Local::now().format("%H:%M").to_string();
+        self.meeting_end_time = now();
+        self.close_meeting_pause(self.meeting_end_time);
+        self.time_in_meetings = self.meeting_minutes(self.meeting_end_time) as i32;
+        self.record(JournalEvent::MeetingEnded {
+            time: self.meeting_end_time,
+            minutes: self.time_in_meetings,
+        });
         let meeting = MeetingList {
             meeting_name: self.meeting_name.clone(),
-            meeting_start_time: self.meeting_start_time.clone(),
-            meeting_end_time: self.meeting_end_time.clone(),
-            time_in_meeting: self.time_in_meetings.clone(),
+            meeting_start_time: self.meeting_start_time,
+            meeting_end_time: self.meeting_end_time,
+            time_in_meeting: self.time_in_meetings,
+            project: self.meeting_project.take(),
+            billable: std::mem::replace(&mut self.meeting_billable, true),
+            notes: std::mem::take(&mut self.meeting_notes),
+            action_items: std::mem::take(&mut self.meeting_action_items),
+            pauses: std::mem::take(&mut self.meeting_pauses),
         };
         //self.total_time_in_meetings += self.time_in_meetings;
         self.meeting_list.push(meeting);
+        self.meeting_ended();
         self.time_in_meetings = 0;
     }
 
+    /// Today's calendar events that have not ended yet, offered when naming a
+    /// meeting. At most nine, one per digit key.
+    pub fn load_meeting_suggestions(&mut self) {
+        self.meeting_suggestions = match Calendar::load(&self.config.calendar.paths) {
+            Ok(calendar) => calendar
+                .events_on(Local::now().date_naive())
+                .into_iter()
+                .filter(|event| event.end.is_none_or(|end| end > now()))
+                .take(9)
+                .collect(),
+            Err(e) => {
+                self.export_message = Some(format!("Reading the calendar failed: {}", e));
+                Vec::new()
+            }
+        };
+    }
+
+    /// Start the meeting behind suggestion `index` (0-based). Returns false if
+    /// there is no such suggestion.
+    pub fn pick_meeting_suggestion(&mut self, index: usize) -> bool {
+        let Some(event) = self.meeting_suggestions.get(index) else {
+            return false;
+        };
+        self.meeting_name_input = event.summary.clone();
+        self.meeting_suggestions.clear();
+        self.start_meeting();
+        true
+    }
+
+    /// Add the timed events of `date` from the calendars in `paths` (the
+    /// configured ones if empty) to the meeting list, skipping those already
+    /// in it. Returns the number of meetings added.
+    pub fn import_calendar_meetings(&mut self, date: NaiveDate, paths: &[String]) -> Result<usize> {
+        let paths = if paths.is_empty() { &self.config.calendar.paths } else { paths };
+        if paths.is_empty() {
+            return Err(anyhow!("no calendar configured, set [calendar] paths in the config"));
+        }
+        let calendar = Calendar::load(paths)?;
+        let mut added = 0;
+        for event in calendar.events_on(date) {
+            let (Some(start), Some(end)) = (event.start, event.end) else {
+                continue;
+            };
+            let known = self
+                .meeting_list
+                .iter()
+                .any(|m| m.meeting_name == event.summary && m.meeting_start_time == start);
+            if known {
+                continue;
+            }
+            let minutes = minutes_between(start, end) as i32;
+            self.record(JournalEvent::MeetingAdded {
+                name: event.summary.clone(),
+                start,
+                end,
+                minutes,
+            });
+            self.meeting_list.push(MeetingList {
+                meeting_name: event.summary,
+                meeting_start_time: start,
+                meeting_end_time: end,
+                time_in_meeting: minutes,
+                project: None,
+                billable: true,
+                notes: String::new(),
+                action_items: Vec::new(),
+                pauses: Vec::new(),
+            });
+            added += 1;
+        }
+        Ok(added)
+    }
+
+    /// [`Self::import_calendar_meetings`] for today, reported in the footer.
+    pub fn import_todays_meetings(&mut self) {
+        self.export_message = Some(match self.import_calendar_meetings(Local::now().date_naive(), &[]) {
+            Ok(0) => "No new meetings in the calendar".to_string(),
+            Ok(added) => format!("Imported {} meetings from the calendar", added),
+            Err(e) => format!("Calendar import failed: {}", e),
+        });
+    }
+
     pub fn previous_list_item(&mut self) {
         let i = match self.list_state.selected() {
             Some(i) => {
@@ -265,54 +621,858 @@ impl App {
     }
 
     pub fn get_data_len(&self) -> usize {
-        match read_json() {
-            Ok(json_response) => {
-                json_response.len()
-            }
-            Err(_) => {
-                0
-            }
-        }
+        self.edit_history_tab.len()
     }
 
     pub fn start_focus_time(&mut self) {
+        self.start_focus_time_with(true);
+    }
+
+    pub fn start_focus_time_with(&mut self, billable: bool) {
         self.focus = true;
-        self.focus_time_start = Local::now().format("%H:%M").to_string();
+        self.focus_time_start = now();
+        self.focus_project = self.running_project();
+        self.focus_billable = billable;
+        self.focus_paused = false;
+        self.focus_pauses = Vec::new();
+        self.record(JournalEvent::FocusStarted {
+            time: self.focus_time_start,
+            project: self.focus_project.clone(),
+            billable,
+        });
     }
 
     pub fn end_focus_time(&mut self) {
         self.focus = false;
-        self.focus_time_end = Local::now().format("%H:%M").to_string();
+        self.focus_time_end = now();
+        self.close_focus_pause(self.focus_time_end);
+        self.focus_time = self.focus_minutes(self.focus_time_end) as u64;
+        self.record(JournalEvent::FocusEnded {
+            time: self.focus_time_end,
+            minutes: self.focus_time,
+        });
         let focus_time = FocusTime {
-            focus_time_start: self.focus_time_start.clone(),
-            focus_time_end: self.focus_time_end.clone(),
+            focus_time_start: self.focus_time_start,
+            focus_time_end: self.focus_time_end,
             focus_time: self.focus_time as i32,
+            project: self.focus_project.take(),
+            billable: std::mem::replace(&mut self.focus_billable, true),
+            pauses: std::mem::take(&mut self.focus_pauses),
         };
         self.focus_time_list.push(focus_time);
         self.focus_time_total += self.focus_time;
         self.focus_time = 0;
     }
 
+    /// Minutes of the running meeting until `at`, without its pauses.
+    pub fn meeting_minutes(&self, at: Timestamp) -> i64 {
+        let open = self.meeting_paused.then_some(self.meeting_pause_start);
+        minutes_between(self.meeting_start_time, at) - paused_minutes(&self.meeting_pauses, open, at)
+    }
+
+    /// Minutes of the running focus session until `at`, without its pauses.
+    pub fn focus_minutes(&self, at: Timestamp) -> i64 {
+        let open = self.focus_paused.then_some(self.focus_pause_start);
+        minutes_between(self.focus_time_start, at) - paused_minutes(&self.focus_pauses, open, at)
+    }
+
+    /// Stop counting the running meeting until it is resumed. Returns false
+    /// if no meeting runs or it is paused already.
+    pub fn pause_meeting(&mut self) -> bool {
+        if !self.meeting_running || self.meeting_paused {
+            return false;
+        }
+        self.meeting_paused = true;
+        self.meeting_pause_start = now();
+        self.record(JournalEvent::MeetingPaused { time: self.meeting_pause_start });
+        true
+    }
+
+    pub fn resume_meeting(&mut self) -> bool {
+        if !self.meeting_running || !self.meeting_paused {
+            return false;
+        }
+        let time = now();
+        self.close_meeting_pause(time);
+        self.record(JournalEvent::MeetingResumed { time });
+        true
+    }
+
+    /// Stop counting the running focus session until it is resumed. Returns
+    /// false if no session runs or it is paused already.
+    pub fn pause_focus_time(&mut self) -> bool {
+        if !self.focus || self.focus_paused {
+            return false;
+        }
+        self.focus_paused = true;
+        self.focus_pause_start = now();
+        self.record(JournalEvent::FocusPaused { time: self.focus_pause_start });
+        true
+    }
+
+    pub fn resume_focus_time(&mut self) -> bool {
+        if !self.focus || !self.focus_paused {
+            return false;
+        }
+        let time = now();
+        self.close_focus_pause(time);
+        self.record(JournalEvent::FocusResumed { time });
+        true
+    }
+
+    /// Pause the running meeting, or resume it if it is paused.
+    pub fn toggle_meeting_pause(&mut self) -> bool {
+        if self.meeting_paused {
+            self.resume_meeting()
+        } else {
+            self.pause_meeting()
+        }
+    }
+
+    /// Pause the running focus session, or resume it if it is paused.
+    pub fn toggle_focus_pause(&mut self) -> bool {
+        if self.focus_paused {
+            self.resume_focus_time()
+        } else {
+            self.pause_focus_time()
+        }
+    }
+
+    // End a pause of the running meeting at `at`; ending the meeting ends
+    // its pause as well.
+    fn close_meeting_pause(&mut self, at: Timestamp) {
+        if std::mem::replace(&mut self.meeting_paused, false) {
+            self.meeting_pauses.push(Pause {
+                start: self.meeting_pause_start,
+                end: at,
+            });
+        }
+    }
+
+    fn close_focus_pause(&mut self, at: Timestamp) {
+        if std::mem::replace(&mut self.focus_paused, false) {
+            self.focus_pauses.push(Pause {
+                start: self.focus_pause_start,
+                end: at,
+            });
+        }
+    }
+
+    /// Whether the running meeting, or else the running focus session, is
+    /// billable. `None` if neither runs.
+    pub fn running_billable(&self) -> Option<bool> {
+        if self.meeting_running {
+            Some(self.meeting_billable)
+        } else if self.focus {
+            Some(self.focus_billable)
+        } else {
+            None
+        }
+    }
+
+    /// Mark the running meeting and focus session as billable or not.
+    pub fn set_billable(&mut self, billable: bool) -> bool {
+        if self.running_billable().is_none() {
+            return false;
+        }
+        self.meeting_billable = billable;
+        self.focus_billable = billable;
+        self.record(JournalEvent::BillableSet { billable });
+        true
+    }
+
+    pub fn toggle_billable(&mut self) {
+        if let Some(billable) = self.running_billable() {
+            self.set_billable(!billable);
+        }
+    }
+
+    pub fn start_break(&mut self) {
+        if self.break_running {
+            return;
+        }
+        self.break_running = true;
+        self.break_start_time = now();
+        self.time_in_break = 0;
+        self.record(JournalEvent::BreakStarted {
+            time: self.break_start_time,
+        });
+    }
+
+    pub fn end_break(&mut self) {
+        if !self.break_running {
+            return;
+        }
+        self.break_running = false;
+        let end = now();
+        self.time_in_break = minutes_between(self.break_start_time, end) as i32;
+        self.record(JournalEvent::BreakEnded {
+            time: end,
+            minutes: self.time_in_break,
+        });
+        self.break_list.push(BreakTime {
+            break_start_time: self.break_start_time,
+            break_end_time: end,
+            time_in_break: self.time_in_break,
+        });
+        self.time_in_break = 0;
+    }
+
+    pub fn running_project(&self) -> Option<String> {
+        self.project_running.then(|| self.project_name.clone())
+    }
+
+    /// Book time to the project in `project_input`, written "project" or
+    /// "project/task". A running project ends first. Returns false if the
+    /// input names no project.
+    pub fn start_project(&mut self) -> bool {
+        let input = std::mem::take(&mut self.project_input);
+        let (project, task) = match input.split_once('/') {
+            Some((project, task)) => (project.trim(), Some(task.trim()).filter(|t| !t.is_empty())),
+            None => (input.trim(), None),
+        };
+        if project.is_empty() {
+            return false;
+        }
+        self.start_project_named(project.to_string(), task.map(str::to_string));
+        self.currently_editing = None;
+        true
+    }
+
+    pub fn start_project_named(&mut self, project: String, task: Option<String>) {
+        self.end_project();
+        self.project_running = true;
+        self.project_name = project;
+        self.project_task = task;
+        self.project_start_time = now();
+        self.time_in_project = 0;
+        self.record(JournalEvent::ProjectStarted {
+            project: self.project_name.clone(),
+            task: self.project_task.clone(),
+            time: self.project_start_time,
+        });
+    }
+
+    /// Start the configured project number `index` (0-based), without a task.
+    pub fn pick_project(&mut self, index: usize) -> bool {
+        let Some(project) = self.config.projects.get(index) else {
+            return false;
+        };
+        self.project_input = project.name.clone();
+        self.start_project()
+    }
+
+    pub fn end_project(&mut self) {
+        if !self.project_running {
+            return;
+        }
+        self.project_running = false;
+        let end = now();
+        self.time_in_project = minutes_between(self.project_start_time, end) as i32;
+        self.record(JournalEvent::ProjectEnded {
+            time: end,
+            minutes: self.time_in_project,
+        });
+        self.project_list.push(ProjectTime {
+            project: self.project_name.clone(),
+            task: self.project_task.take(),
+            project_start_time: self.project_start_time,
+            project_end_time: end,
+            time_in_project: self.time_in_project,
+        });
+        self.time_in_project = 0;
+    }
+
+    /// "project" or "project / task" of the running project.
+    pub fn project_label(&self) -> String {
+        match &self.project_task {
+            Some(task) => format!("{} / {}", self.project_name, task),
+            None => self.project_name.clone(),
+        }
+    }
+
+    /// The meetings in the notes tab: the running one, today's newest first,
+    /// then those of the stored days.
+    pub fn note_entries(&self) -> Vec<NoteEntry<'_>> {
+        let mut entries = Vec::new();
+        if self.meeting_running {
+            entries.push(NoteEntry {
+                target: NoteTarget::Running,
+                name: &self.meeting_name,
+                start: self.meeting_start_time,
+                notes: &self.meeting_notes,
+                action_items: &self.meeting_action_items,
+            });
+        }
+        entries.extend(self.meeting_list.iter().enumerate().rev().map(|(index, meeting)| NoteEntry {
+            target: NoteTarget::Today(index),
+            name: &meeting.meeting_name,
+            start: meeting.meeting_start_time,
+            notes: &meeting.notes,
+            action_items: &meeting.action_items,
+        }));
+        entries.extend(self.meeting_notes_tab.stored_entries());
+        entries
+    }
+
+    /// Open the editor on the notes of the selected meeting.
+    pub fn open_notes(&mut self) -> bool {
+        let entries = self.note_entries();
+        let Some(entry) = entries.get(self.meeting_notes_tab.selected.min(entries.len().saturating_sub(1))) else {
+            return false;
+        };
+        let editor = (entry.target.clone(), NoteEditor::new(entry.notes));
+        self.meeting_notes_tab.editor = Some(editor);
+        self.meeting_notes_tab.dirty = false;
+        self.tab = Tab::MeetingNotesTab;
+        self.current_screen = CurrentScreen::EditingNotes;
+        self.currently_editing = Some(CurrentlyEditing::Notes);
+        true
+    }
+
+    pub fn edit_notes(&mut self, code: KeyCode) {
+        if let Some((_, editor)) = &mut self.meeting_notes_tab.editor {
+            if editor.handle_key(code) {
+                self.meeting_notes_tab.dirty = true;
+                self.meeting_notes_tab.edited_at = Instant::now();
+            }
+        }
+    }
+
+    /// Save the notes of today's meetings once typing paused for a few
+    /// seconds, so little is lost in a crash. Stored days are only written
+    /// when the editor closes.
+    pub fn autosave_notes(&mut self) {
+        let idle = self.meeting_notes_tab.edited_at.elapsed() >= NOTES_AUTOSAVE_DELAY;
+        match &self.meeting_notes_tab.editor {
+            Some((NoteTarget::Running | NoteTarget::Today(_), _)) if idle => self.save_notes(),
+            _ => {}
+        }
+    }
+
+    /// Write the notes in the editor to their meeting, if they changed.
+    /// Today's are journalled, replacing the last save of the same meeting.
+    pub fn save_notes(&mut self) {
+        if !self.meeting_notes_tab.dirty {
+            return;
+        }
+        let Some((target, editor)) = &self.meeting_notes_tab.editor else {
+            return;
+        };
+        let notes = editor.text();
+        match target.clone() {
+            NoteTarget::Running if self.meeting_running => {
+                self.meeting_notes = notes.clone();
+                self.record_notes(None, notes);
+            }
+            NoteTarget::Running => {}
+            NoteTarget::Today(index) => {
+                if let Some(meeting) = self.meeting_list.get_mut(index) {
+                    meeting.notes = notes.clone();
+                    self.record_notes(Some(index), notes);
+                }
+            }
+            NoteTarget::Stored { date, index } => {
+                let Some(day) = self.meeting_notes_tab.history.iter_mut().find(|day| day.date == date) else {
+                    return;
+                };
+                if let Some(meeting) = day.meetings.get_mut(index) {
+                    meeting.notes = notes;
+                    if let Err(e) = open_default().and_then(|store| store.upsert(day)) {
+                        self.export_message = Some(format!("Saving the notes failed: {}", e));
+                        return;
+                    }
+                }
+            }
+        }
+        self.meeting_notes_tab.dirty = false;
+    }
+
+    /// Save and close the editor.
+    pub fn close_notes(&mut self) {
+        self.save_notes();
+        self.meeting_notes_tab.editor = None;
+        self.current_screen = CurrentScreen::Main;
+        self.currently_editing = None;
+    }
+
+    /// Open the input for an action item of the selected meeting.
+    pub fn begin_action_item(&mut self) -> bool {
+        if self.meeting_notes_tab.view != NotesView::Meetings || self.note_entries().is_empty() {
+            return false;
+        }
+        self.action_item_input = String::new();
+        self.current_screen = CurrentScreen::AddingActionItem;
+        self.currently_editing = Some(CurrentlyEditing::ActionItem);
+        true
+    }
+
+    /// Add the action item in `action_item_input` to the selected meeting.
+    /// Returns false and keeps the input open if it cannot be read.
+    pub fn add_action_item(&mut self) -> bool {
+        let item = match ActionItem::parse(&self.action_item_input) {
+            Ok(item) => item,
+            Err(e) => {
+                self.export_message = Some(e.to_string());
+                return false;
+            }
+        };
+        let entries = self.note_entries();
+        let Some(entry) = entries.get(self.meeting_notes_tab.selected.min(entries.len().saturating_sub(1))) else {
+            return false;
+        };
+        let target = entry.target.clone();
+        let Some(items) = self.action_items_mut(&target) else {
+            return false;
+        };
+        items.push(item.clone());
+        let meeting = journal_meeting(&target);
+        self.keep_action_items(&target, JournalEvent::ActionItemAdded { meeting, item });
+        self.action_item_input = String::new();
+        self.currently_editing = None;
+        true
+    }
+
+    /// Mark the selected action item of the list done, or open again.
+    pub fn toggle_action_item(&mut self) {
+        let entries = self.note_entries();
+        let items = self.meeting_notes_tab.action_items(&entries);
+        let Some(entry) = items.get(self.meeting_notes_tab.item_selected.min(items.len().saturating_sub(1))) else {
+            return;
+        };
+        let (target, index, done) = (entry.target.clone(), entry.index, !entry.item.done);
+        let Some(item) = self.action_items_mut(&target).and_then(|items| items.get_mut(index)) else {
+            return;
+        };
+        item.done = done;
+        let event = JournalEvent::ActionItemDone {
+            meeting: journal_meeting(&target),
+            item: index,
+            done,
+        };
+        self.keep_action_items(&target, event);
+    }
+
+    /// Open the search of the action item list.
+    pub fn begin_action_item_search(&mut self) {
+        self.meeting_notes_tab.view = NotesView::ActionItems;
+        self.current_screen = CurrentScreen::SearchingActionItems;
+        self.currently_editing = Some(CurrentlyEditing::ActionItemSearch);
+    }
+
+    /// Open action items of all meetings whose due date has passed.
+    pub fn overdue_action_items(&self) -> usize {
+        let today = Local::now().date_naive();
+        let stored = self.meeting_notes_tab.history.iter().flat_map(|day| &day.meetings);
+        self.meeting_action_items
+            .iter()
+            .chain(self.meeting_list.iter().chain(stored).flat_map(|meeting| &meeting.action_items))
+            .filter(|item| item.is_overdue(today))
+            .count()
+    }
+
+    fn action_items_mut(&mut self, target: &NoteTarget) -> Option<&mut Vec<ActionItem>> {
+        match target {
+            NoteTarget::Running if self.meeting_running => Some(&mut self.meeting_action_items),
+            NoteTarget::Running => None,
+            NoteTarget::Today(index) => self.meeting_list.get_mut(*index).map(|meeting| &mut meeting.action_items),
+            NoteTarget::Stored { date, index } => self
+                .meeting_notes_tab
+                .history
+                .iter_mut()
+                .find(|day| &day.date == date)?
+                .meetings
+                .get_mut(*index)
+                .map(|meeting| &mut meeting.action_items),
+        }
+    }
+
+    // Keep a change to the action items of `target`: today's meetings are
+    // journalled, those of stored days are saved with their day.
+    fn keep_action_items(&mut self, target: &NoteTarget, event: JournalEvent) {
+        let NoteTarget::Stored { date, .. } = target else {
+            self.record(event);
+            return;
+        };
+        let Some(day) = self.meeting_notes_tab.history.iter().find(|day| &day.date == date) else {
+            return;
+        };
+        if let Err(e) = open_default().and_then(|store| store.upsert(day)) {
+            self.export_message = Some(format!("Saving the action items failed: {}", e));
+        }
+    }
+
+    // The running meeting just became the last of today's list; an editor on
+    // it keeps going there.
+    fn meeting_ended(&mut self) {
+        if let Some((target @ NoteTarget::Running, _)) = &mut self.meeting_notes_tab.editor {
+            *target = NoteTarget::Today(self.meeting_list.len() - 1);
+        }
+    }
+
+    /// Suggest this month as the range of a CSV export.
+    pub fn prefill_export_range(&mut self) {
+        let today = Local::now().date_naive();
+        let first = today.with_day(1).unwrap_or(today);
+        self.export_range_input = format!("{} {}", first, today);
+    }
+
+    /// Export the range "YYYY-MM-DD YYYY-MM-DD" in `export_range_input` as CSV
+    /// to `data/export`. Returns false and keeps the popup open if the input is
+    /// not a valid range.
+    pub fn save_export_csv(&mut self) -> bool {
+        let dates: Option<Vec<NaiveDate>> = self
+            .export_range_input
+            .split_whitespace()
+            .map(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+            .collect();
+        let Some([from, to]) = dates.as_deref() else {
+            return false;
+        };
+        let path = default_export_path(*from, *to);
+        let result = std::fs::create_dir_all(DEFAULT_EXPORT_DIR)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| Ok(std::fs::File::create(&path)?))
+            .and_then(|file| export_csv(*from, *to, &self.config.breaks, &self.config.csv, file));
+        self.export_message = Some(match result {
+            Ok(days) => format!("Exported {} days to {}", days, path.display()),
+            Err(e) => format!("CSV export failed: {}", e),
+        });
+        self.export_range_input = String::new();
+        self.currently_editing = None;
+        true
+    }
+
+    /// Save the report shown in the report tab as Markdown and HTML.
+    pub fn write_report_files(&mut self) {
+        self.export_message = Some(match self.report_tab.write_files() {
+            Ok(paths) => format!(
+                "Wrote {}",
+                paths.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(" and ")
+            ),
+            Err(e) => format!("Writing the report failed: {}", e),
+        });
+    }
+
+    /// Write the notes of today's finished meetings and of the stored days
+    /// as Markdown to the configured notes directory.
+    pub fn write_note_files(&mut self) {
+        self.save_notes();
+        let today = self.worktime(self.starttime().with_timezone(&Local).date_naive(), now());
+        let mut days = self.meeting_notes_tab.history.clone();
+        days.retain(|day| day.date != today.date);
+        days.push(today);
+        let notes = &self.config.notes;
+        self.export_message = Some(match write_notes(&days, notes.dir_path(), notes.layout) {
+            Ok(paths) => format!("Wrote {} notes files to {}", paths.len(), notes.dir_path().display()),
+            Err(e) => format!("Writing the notes failed: {}", e),
+        });
+    }
+
     pub fn chache_focus_time(&mut self) {
         let mut focus_cache_file = std::fs::File::create(".tmp_cache/focus_cache.bin").unwrap();
         let export_focus: String = self.focus.to_string() + &','.to_string() + &self.focus_time.to_string();
         focus_cache_file.write_all(export_focus.as_bytes()).unwrap();
     }
 
-    pub fn export_json(&self) -> Result<()> {
-        let date = Local::now().format("%Y-%m-%d").to_string();
-        let mut worktime = Worktime::new(
-            date,
-            self.starttime_pairs
-                .get(&self.starttime_key)
-                .unwrap()
-                .clone(),
-            self.endtime_pairs.get(&self.endtime_key).unwrap().clone(),
-            self.meeting_list.clone(),
-            self.focus_time_list.clone(),
-        );
+    fn record(&self, event: JournalEvent) {
+        // The journal is a safety net; a failed write must not stop the tracker.
+        let _ = self.journal.record(event);
+    }
 
+    // Only the latest text counts, so a save right after one of the same
+    // meeting takes its place.
+    fn record_notes(&self, meeting: Option<usize>, notes: String) {
+        let event = JournalEvent::MeetingNotesSaved { meeting, notes };
+        let _ = self.journal.record_superseding(event, |last| {
+            matches!(last, JournalEvent::MeetingNotesSaved { meeting: saved, .. } if *saved == meeting)
+        });
+    }
+
+    /// Replay a journal into this (fresh) app. Sessions that were still running
+    /// when the journal stopped get the minutes elapsed until `until`.
+    pub fn restore_from_journal(&mut self, entries: &[JournalEntry], until: Timestamp) {
+        for entry in entries {
+            match &entry.event {
+                JournalEvent::StartSet { time } => {
+                    self.starttime_pairs.insert(self.starttime_key.clone(), *time);
+                }
+                JournalEvent::EndSet { time } => {
+                    self.endtime_pairs.insert(self.endtime_key.clone(), *time);
+                }
+                JournalEvent::MeetingStarted { name, time, project, billable } => {
+                    self.meeting_running = true;
+                    self.meeting_name = name.clone();
+                    self.meeting_start_time = *time;
+                    self.meeting_project = project.clone();
+                    self.meeting_billable = *billable;
+                    self.meeting_notes = String::new();
+                    self.meeting_action_items = Vec::new();
+                    self.meeting_paused = false;
+                    self.meeting_pauses = Vec::new();
+                }
+                JournalEvent::MeetingPaused { time } => {
+                    self.meeting_paused = true;
+                    self.meeting_pause_start = *time;
+                }
+                JournalEvent::MeetingResumed { time } => {
+                    self.close_meeting_pause(*time);
+                }
+                JournalEvent::MeetingEnded { time, minutes } => {
+                    self.meeting_running = false;
+                    self.close_meeting_pause(*time);
+                    self.meeting_list.push(MeetingList {
+                        meeting_name: self.meeting_name.clone(),
+                        meeting_start_time: self.meeting_start_time,
+                        meeting_end_time: *time,
+                        time_in_meeting: *minutes,
+                        project: self.meeting_project.take(),
+                        billable: std::mem::replace(&mut self.meeting_billable, true),
+                        notes: std::mem::take(&mut self.meeting_notes),
+                        action_items: std::mem::take(&mut self.meeting_action_items),
+                        pauses: std::mem::take(&mut self.meeting_pauses),
+                    });
+                }
+                JournalEvent::MeetingAdded { name, start, end, minutes } => {
+                    self.meeting_list.push(MeetingList {
+                        meeting_name: name.clone(),
+                        meeting_start_time: *start,
+                        meeting_end_time: *end,
+                        time_in_meeting: *minutes,
+                        project: None,
+                        billable: true,
+                        notes: String::new(),
+                        action_items: Vec::new(),
+                        pauses: Vec::new(),
+                    });
+                }
+                JournalEvent::FocusStarted { time, project, billable } => {
+                    self.focus = true;
+                    self.focus_time_start = *time;
+                    self.focus_project = project.clone();
+                    self.focus_billable = *billable;
+                    self.focus_paused = false;
+                    self.focus_pauses = Vec::new();
+                }
+                JournalEvent::FocusPaused { time } => {
+                    self.focus_paused = true;
+                    self.focus_pause_start = *time;
+                }
+                JournalEvent::FocusResumed { time } => {
+                    self.close_focus_pause(*time);
+                }
+                JournalEvent::FocusEnded { time, minutes } => {
+                    self.focus = false;
+                    self.close_focus_pause(*time);
+                    self.focus_time_list.push(FocusTime {
+                        focus_time_start: self.focus_time_start,
+                        focus_time_end: *time,
+                        focus_time: *minutes as i32,
+                        project: self.focus_project.take(),
+                        billable: std::mem::replace(&mut self.focus_billable, true),
+                        pauses: std::mem::take(&mut self.focus_pauses),
+                    });
+                    self.focus_time_total += minutes;
+                }
+                JournalEvent::BillableSet { billable } => {
+                    self.meeting_billable = *billable;
+                    self.focus_billable = *billable;
+                }
+                JournalEvent::MeetingNotesSaved { meeting: None, notes } => {
+                    self.meeting_notes = notes.clone();
+                }
+                JournalEvent::MeetingNotesSaved { meeting: Some(index), notes } => {
+                    if let Some(meeting) = self.meeting_list.get_mut(*index) {
+                        meeting.notes = notes.clone();
+                    }
+                }
+                JournalEvent::ActionItemAdded { meeting, item } => {
+                    if let Some(items) = self.action_items_mut(&journal_target(*meeting)) {
+                        items.push(item.clone());
+                    }
+                }
+                JournalEvent::ActionItemDone { meeting, item, done } => {
+                    let items = self.action_items_mut(&journal_target(*meeting));
+                    if let Some(item) = items.and_then(|items| items.get_mut(*item)) {
+                        item.done = *done;
+                    }
+                }
+                JournalEvent::BreakStarted { time } => {
+                    self.break_running = true;
+                    self.break_start_time = *time;
+                }
+                JournalEvent::BreakEnded { time, minutes } => {
+                    self.break_running = false;
+                    self.break_list.push(BreakTime {
+                        break_start_time: self.break_start_time,
+                        break_end_time: *time,
+                        time_in_break: *minutes,
+                    });
+                }
+                JournalEvent::ProjectStarted { project, task, time } => {
+                    self.project_running = true;
+                    self.project_name = project.clone();
+                    self.project_task = task.clone();
+                    self.project_start_time = *time;
+                }
+                JournalEvent::ProjectEnded { time, minutes } => {
+                    self.project_running = false;
+                    self.project_list.push(ProjectTime {
+                        project: self.project_name.clone(),
+                        task: self.project_task.take(),
+                        project_start_time: self.project_start_time,
+                        project_end_time: *time,
+                        time_in_project: *minutes,
+                    });
+                }
+            }
+        }
+        if self.meeting_running {
+            self.time_in_meetings = self.meeting_minutes(until) as i32;
+        }
+        if self.focus {
+            self.focus_time = self.focus_minutes(until) as u64;
+        }
+        if self.break_running {
+            self.time_in_break = minutes_between(self.break_start_time, until) as i32;
+        }
+        if self.project_running {
+            self.time_in_project = minutes_between(self.project_start_time, until) as i32;
+        }
+    }
+
+    /// End a running meeting, focus session, break and project at `at`, as if
+    /// the keys had been pressed then.
+    pub fn close_running_sessions(&mut self, at: Timestamp) {
+        if self.meeting_running {
+            self.meeting_running = false;
+            self.close_meeting_pause(at);
+            let minutes = self.meeting_minutes(at);
+            self.meeting_list.push(MeetingList {
+                meeting_name: self.meeting_name.clone(),
+                meeting_start_time: self.meeting_start_time,
+                meeting_end_time: at,
+                time_in_meeting: minutes as i32,
+                project: self.meeting_project.take(),
+                billable: std::mem::replace(&mut self.meeting_billable, true),
+                notes: std::mem::take(&mut self.meeting_notes),
+                action_items: std::mem::take(&mut self.meeting_action_items),
+                pauses: std::mem::take(&mut self.meeting_pauses),
+            });
+            self.time_in_meetings = 0;
+        }
+        if self.focus {
+            self.focus = false;
+            self.close_focus_pause(at);
+            self.focus_time = self.focus_minutes(at) as u64;
+            self.focus_time_list.push(FocusTime {
+                focus_time_start: self.focus_time_start,
+                focus_time_end: at,
+                focus_time: self.focus_time as i32,
+                project: self.focus_project.take(),
+                billable: std::mem::replace(&mut self.focus_billable, true),
+                pauses: std::mem::take(&mut self.focus_pauses),
+            });
+            self.focus_time_total += self.focus_time;
+            self.focus_time = 0;
+        }
+        if self.break_running {
+            self.break_running = false;
+            self.break_list.push(BreakTime {
+                break_start_time: self.break_start_time,
+                break_end_time: at,
+                time_in_break: minutes_between(self.break_start_time, at) as i32,
+            });
+            self.time_in_break = 0;
+        }
+        if self.project_running {
+            self.project_running = false;
+            self.project_list.push(ProjectTime {
+                project: self.project_name.clone(),
+                task: self.project_task.take(),
+                project_start_time: self.project_start_time,
+                project_end_time: at,
+                time_in_project: minutes_between(self.project_start_time, at) as i32,
+            });
+            self.time_in_project = 0;
+        }
+    }
+
+    /// Save a journalled day that was never exported. Sessions still running
+    /// end with the last recorded event.
+    pub fn export_unfinished(config: Config, entries: &[JournalEntry]) -> Result<()> {
+        let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+            return Ok(());
+        };
+        let date = first.recorded_at.with_timezone(&Local).date_naive();
+        let mut unfinished = App::with_config(config);
+        unfinished.restore_from_journal(entries, last.recorded_at);
+        unfinished.close_running_sessions(last.recorded_at);
+        unfinished.export_day(date, last.recorded_at)
+    }
+
+    /// Export the running day. It is filed under the date it started on, so a
+    /// night shift stays one record. Sessions still running end now.
+    pub fn export_json(&mut self) -> Result<()> {
+        let at = now();
+        self.close_running_sessions(at);
+        let start_date = self.starttime().with_timezone(&Local).date_naive();
+        self.export_day(start_date, at)
+    }
+
+    /// Export the tracked day under `date`. A missing start falls back to the
+    /// default start on that date, a missing end to `fallback_end`.
+    /// With `export_on_save` the day's notes are written too; failing that
+    /// is only reported, as the day itself is stored by then.
+    pub fn export_day(&self, date: NaiveDate, fallback_end: Timestamp) -> Result<()> {
+        let mut worktime = self.worktime(date, fallback_end);
         worktime.export_json()?;
+        self.journal.clear()?;
+        let notes = &self.config.notes;
+        if notes.export_on_save {
+            if let Err(e) = write_notes(&[worktime], notes.dir_path(), notes.layout) {
+                eprintln!("Saved the day {}, but writing its notes failed: {:#}", date, e);
+            }
+        }
         Ok(())
     }
+
+    /// The tracked day as it would be stored under `date`.
+    fn worktime(&self, date: NaiveDate, fallback_end: Timestamp) -> Worktime {
+        let starttime = self
+            .starttime_pairs
+            .get(&self.starttime_key)
+            .copied()
+            .or_else(|| parse_time(&self.default_starttime).map(|t| local_timestamp(date, t)))
+            .unwrap_or(fallback_end);
+        let endtime = self
+            .endtime_pairs
+            .get(&self.endtime_key)
+            .copied()
+            .unwrap_or(fallback_end);
+        Worktime::new(
+            date.format("%Y-%m-%d").to_string(),
+            starttime,
+            endtime,
+            self.meeting_list.clone(),
+            self.focus_time_list.clone(),
+            self.break_list.clone(),
+            self.project_list.clone(),
+        )
+    }
+}
+
+// How the journal refers to the meeting at `target`: `None` is the running one.
+fn journal_meeting(target: &NoteTarget) -> Option<usize> {
+    match target {
+        NoteTarget::Today(index) => Some(*index),
+        _ => None,
+    }
+}
+
+fn journal_target(meeting: Option<usize>) -> NoteTarget {
+    match meeting {
+        Some(index) => NoteTarget::Today(index),
+        None => NoteTarget::Running,
+    }
 }
@@ -0,0 +1,81 @@
+use crate::config::Config;
+use crate::model::Worktime;
+use chrono::{Duration, NaiveDate};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayBalance {
+    pub date: NaiveDate,
+    pub worked: i64,
+    pub target: i64,
+    /// Balance after this day, corrections included.
+    pub running: i64,
+}
+
+impl DayBalance {
+    pub fn delta(&self) -> i64 {
+        self.worked - self.target
+    }
+}
+
+/// The overtime account: every day from the start date up to `until`
+/// (exclusive) counts its worked minutes against the scheduled target, so a
+/// workday without a record counts as a missing day.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Balance {
+    pub opening: i64,
+    pub corrections: i64,
+    pub days: Vec<DayBalance>,
+}
+
+impl Balance {
+    pub fn calculate(worktime: &[Worktime], config: &Config, until: NaiveDate) -> Self {
+        let mut worked: HashMap<NaiveDate, i64> = HashMap::new();
+        for day in worktime {
+            if let Ok(date) = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d") {
+                *worked.entry(date).or_default() += day.worked_minutes(&config.breaks);
+            }
+        }
+
+        let settings = &config.balance;
+        let Some(start) = settings.start_date.or_else(|| worked.keys().min().copied()) else {
+            return Self {
+                opening: settings.opening_minutes,
+                ..Self::default()
+            };
+        };
+
+        let mut balance = Self {
+            opening: settings.opening_minutes,
+            corrections: 0,
+            days: Vec::new(),
+        };
+        let mut running = settings.opening_minutes;
+        let mut date = start;
+        while date < until {
+            let worked = worked.get(&date).copied().unwrap_or(0);
+            let target = config.schedule.target_minutes(date);
+            let corrections: i64 = settings
+                .corrections
+                .iter()
+                .filter(|c| c.date == date)
+                .map(|c| c.minutes)
+                .sum();
+            running += worked - target + corrections;
+            balance.corrections += corrections;
+            balance.days.push(DayBalance {
+                date,
+                worked,
+                target,
+                running,
+            });
+            date += Duration::days(1);
+        }
+        balance
+    }
+
+    /// Balance at the end of the last counted day.
+    pub fn total(&self) -> i64 {
+        self.days.last().map(|d| d.running).unwrap_or(self.opening)
+    }
+}
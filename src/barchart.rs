@@ -3,8 +3,11 @@ use ratatui::{
     layout::Direction, style::{Color, Modifier, Style}, text::{Line, Span}, widgets::{Bar, BarChart, BarGroup, Block, Paragraph}
 };
 
-use crate::calc_time::parse_time;
+use crate::calc_time::{local_date, minutes_between, split_at_midnight};
+use crate::config::Config;
+use chrono::NaiveDate;
 use crate::read_json::read_json;
+use std::collections::BTreeMap;
 
 #[derive(Clone)]
 pub struct TimeData<'a> {
@@ -21,94 +24,94 @@ pub struct BarChartApp<'a> {
 
 impl<'a> BarChartApp<'a> {
     pub fn new() -> Self {
-        match read_json() {
-            Ok(worktime_list) => {
-                let mut worktime_in_min_list = [0, 0, 0, 0, 0];
-                let mut meetingtime_in_min_list = [0, 0, 0, 0, 0];
-                let mut focustime_in_min_list = [0, 0, 0, 0, 0];
-                let mut days_list:[String; 5] =["".to_string(), "".to_string(), "".to_string(), "".to_string(), "".to_string()];
-
-                let mut idx = 0;
-                for worktime in worktime_list.iter().cloned().rev().take(5).rev() {
-                        let date_string = worktime.date;
-                        days_list[idx] = date_string;
-
-                        let start_time = parse_time(&worktime.starttime);
-                        let end_time = parse_time(&worktime.endtime);
-                        let start_minutes = start_time.0 * 60 + start_time.1;
-                        let end_minutes = end_time.0 * 60 + end_time.1;
-                        let worktime_in_min: i32 = end_minutes - start_minutes;
-
-                        worktime_in_min_list[idx] = worktime_in_min as u64;
-    
-                        let total_meeting_time:i32 = worktime.meetings
-                            .into_iter()
-                            .map(|x| x.time_in_meeting)
-                            .sum();
-
-                        meetingtime_in_min_list[idx] = total_meeting_time as u64;
-
-
-                        let total_focus_time:i32 = worktime.focus_time
-                            .into_iter()
-                            .map(|x| x.focus_time)
-                            .sum();
-
-                        focustime_in_min_list[idx] = total_focus_time as u64;
-                        idx += 1;
-                };
-                
-                BarChartApp {
-                    data: [
-                        TimeData {
-                            time: worktime_in_min_list,
-                            label: "Worktime",
-                            bar_style: Style::default().fg(Color::Green),
-                        },
-                        TimeData {
-                            time: meetingtime_in_min_list,
-                            label: "Meetingtime",
-                            bar_style: Style::default().fg(Color::Red),
-                        },
-                        TimeData {
-                            time: focustime_in_min_list,
-                            label: "Focus Time",
-                            bar_style: Style::default().fg(Color::Blue),
-                        },
-                    ].to_vec(),
-                    days: days_list,
-                }   
-                            
-            },
-            Err(_) => {
-                let worktime_in_min_list = [0, 0, 0, 0, 0];
-                let meetingtime_in_min_list = [0, 0, 0, 0, 0];
-                let focustime_in_min_list = [0, 0, 0, 0, 0];
-                let days_list:[String; 5] =["".to_string(), "".to_string(), "".to_string(), "".to_string(), "".to_string()];
-                BarChartApp {
-                    data: [
-                        TimeData {
-                            time: worktime_in_min_list,
-                            label: "Worktime",
-                            bar_style: Style::default().fg(Color::Green),
-                        },
-                        TimeData {
-                            time: meetingtime_in_min_list,
-                            label: "Meetingtime",
-                            bar_style: Style::default().fg(Color::Red),
-                        },
-                        TimeData {
-                            time: focustime_in_min_list,
-                            label: "Focus Time",
-                            bar_style: Style::default().fg(Color::Blue),
-                        },
-                    ].to_vec(),
-                    days: days_list,
+        Self::with_split(false)
+    }
+
+    pub fn empty() -> Self {
+        BarChartApp {
+            data: Vec::new(),
+            days: Default::default(),
+        }
+    }
+
+    /// The last five days. With `split_overnight` a shift across midnight
+    /// counts towards both calendar days instead of the day it started on.
+    pub fn with_split(split_overnight: bool) -> Self {
+        let worktime_list = read_json().unwrap_or_default();
+        let config = Config::load_default().unwrap_or_default();
+        let schedule = config.schedule;
+
+        // date -> [worktime, meetingtime, focus time] in minutes
+        let mut totals: BTreeMap<String, [u64; 3]> = BTreeMap::new();
+        for worktime in worktime_list {
+            let worked = worktime.worked_minutes(&config.breaks).max(0);
+            if split_overnight {
+                // breaks are spread over both days in proportion to their share of the shift
+                let gross = worktime.gross_minutes().max(1);
+                for (date, start, end) in split_at_midnight(worktime.starttime, worktime.endtime) {
+                    totals.entry(date.format("%Y-%m-%d").to_string()).or_default()[0] +=
+                        (minutes_between(start, end) * worked / gross) as u64;
+                }
+                for meeting in &worktime.meetings {
+                    totals.entry(local_date(meeting.meeting_start_time)).or_default()[1] +=
+                        meeting.time_in_meeting.max(0) as u64;
+                }
+                for focus in &worktime.focus_time {
+                    totals.entry(local_date(focus.focus_time_start)).or_default()[2] +=
+                        focus.focus_time.max(0) as u64;
                 }
-            },
+            } else {
+                let day = totals.entry(worktime.date.clone()).or_default();
+                day[0] += worked as u64;
+                day[1] += worktime.meetings.iter().map(|x| x.time_in_meeting.max(0) as u64).sum::<u64>();
+                day[2] += worktime.focus_time.iter().map(|x| x.focus_time.max(0) as u64).sum::<u64>();
+            }
+        }
+
+        let mut worktime_in_min_list = [0, 0, 0, 0, 0];
+        let mut meetingtime_in_min_list = [0, 0, 0, 0, 0];
+        let mut focustime_in_min_list = [0, 0, 0, 0, 0];
+        let mut target_in_min_list = [0, 0, 0, 0, 0];
+        let mut days_list:[String; 5] =["".to_string(), "".to_string(), "".to_string(), "".to_string(), "".to_string()];
+
+        for (idx, (date, [worktime, meetingtime, focustime])) in totals.into_iter().rev().take(5).rev().enumerate() {
+            if let Ok(day) = NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
+                target_in_min_list[idx] = schedule.target_minutes(day).max(0) as u64;
+            }
+            days_list[idx] = date;
+            worktime_in_min_list[idx] = worktime;
+            meetingtime_in_min_list[idx] = meetingtime;
+            focustime_in_min_list[idx] = focustime;
+        }
+
+        BarChartApp {
+            data: [
+                TimeData {
+                    time: worktime_in_min_list,
+                    label: "Worktime",
+                    bar_style: Style::default().fg(Color::Green),
+                },
+                TimeData {
+                    time: meetingtime_in_min_list,
+                    label: "Meetingtime",
+                    bar_style: Style::default().fg(Color::Red),
+                },
+                TimeData {
+                    time: focustime_in_min_list,
+                    label: "Focus Time",
+                    bar_style: Style::default().fg(Color::Blue),
+                },
+                TimeData {
+                    time: target_in_min_list,
+                    label: "Target",
+                    bar_style: Style::default().fg(Color::DarkGray),
+                },
+            ].to_vec(),
+            days: days_list,
         }
     }
-    pub fn new_current(worktime_in_min: u64, time_in_meetings:u64, _focustime_in_min: u64, date: String) -> Self {
+
+    pub fn new_current(worktime_in_min: u64, time_in_meetings:u64, _focustime_in_min: u64, target_in_min: u64, date: String) -> Self {
         BarChartApp {
             data: [
                 TimeData {
@@ -121,12 +124,23 @@ impl<'a> BarChartApp<'a> {
                     label: "Meetingtime",
                     bar_style: Style::default().fg(Color::Red),
                 },
+                TimeData {
+                    time: [target_in_min, 0, 0, 0, 0],
+                    label: "Target",
+                    bar_style: Style::default().fg(Color::DarkGray),
+                },
             ].to_vec(),
             days: [date, "".to_string(), "".to_string(), "".to_string(), "".to_string()],
         }
     }
 }
 
+impl<'a> Default for BarChartApp<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[allow(clippy::cast_precision_loss)]
 pub fn create_groups<'a>(barchart: &'a BarChartApp) -> Vec<BarGroup<'a>> {
     barchart.days
@@ -223,6 +237,10 @@ pub fn draw_legend(block: Block<'static>) -> Paragraph<'static> {
             "- Focus Time",
             Style::default().fg(Color::Blue),
         )),
+        Line::from(Span::styled(
+            "- Target",
+            Style::default().fg(Color::DarkGray),
+        )),
     ];
     
     let paragraph = Paragraph::new(text).block(block);
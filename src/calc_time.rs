@@ -1,16 +1,97 @@
-pub fn parse_time(time: &str) -> (i32, i32) {
-    let mut split = time.split(':');
-    let hour = split.next().unwrap().parse().unwrap();
-    let minutes = split.next().unwrap().parse().unwrap();
-    (hour, minutes)
-}
-
-pub fn calc_endtime(hour: i32, minutes: i32) -> (i32, i32) {
-    let mut hour_tmp = hour + 7;
-    let mut minutes_tmp = minutes + 80;
-    while minutes_tmp >= 60 {
-        hour_tmp += 1;
-        minutes_tmp -= 60;
+use crate::config::{BreakRules, Schedule};
+use crate::model::Timestamp;
+use chrono::{Duration, Local, LocalResult, NaiveDate, NaiveTime, TimeZone};
+
+/// Parse a wall-clock time such as "8:05" or "08:05".
+pub fn parse_time(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M").ok()
+}
+
+/// The local timestamp of `time` on `date`. A wall-clock time skipped by a
+/// DST change maps to the first instant after the jump, a repeated one to the
+/// earlier of its two instants.
+pub fn local_timestamp(date: NaiveDate, time: NaiveTime) -> Timestamp {
+    let naive = date.and_time(time);
+    match Local.from_local_datetime(&naive) {
+        LocalResult::Single(t) => t.fixed_offset(),
+        LocalResult::Ambiguous(earliest, _) => earliest.fixed_offset(),
+        LocalResult::None => Local
+            .from_local_datetime(&(naive + Duration::hours(1)))
+            .earliest()
+            .unwrap_or_else(|| Local.from_utc_datetime(&naive))
+            .fixed_offset(),
+    }
+}
+
+/// A legacy "HH:MM" value stored for the day `date`. Times before `day_start`
+/// belong to the next day, the shift went past midnight.
+pub fn legacy_timestamp(date: &str, time: &str, day_start: Option<Timestamp>) -> Option<Timestamp> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    let time = parse_time(time)?;
+    Some(match day_start {
+        Some(start) => next_after(start, time),
+        None => local_timestamp(date, time),
+    })
+}
+
+/// The first occurrence of the wall-clock `time` at or after `start`.
+pub fn next_after(start: Timestamp, time: NaiveTime) -> Timestamp {
+    let date = start.with_timezone(&Local).date_naive();
+    let candidate = local_timestamp(date, time);
+    if candidate >= start {
+        candidate
+    } else {
+        date.succ_opt().map(|next| local_timestamp(next, time)).unwrap_or(candidate)
     }
-    (hour_tmp, minutes_tmp)
+}
+
+/// A user-entered "HH:MM" as a timestamp of today.
+pub fn parse_today(time: &str) -> Option<Timestamp> {
+    parse_time(time).map(|t| local_timestamp(Local::now().date_naive(), t))
+}
+
+pub fn now() -> Timestamp {
+    Local::now().fixed_offset()
+}
+
+/// Whole minutes from `start` to `end`, never negative.
+pub fn minutes_between(start: Timestamp, end: Timestamp) -> i64 {
+    (end - start).num_minutes().max(0)
+}
+
+/// The local calendar date of `time` as "%Y-%m-%d".
+pub fn local_date(time: Timestamp) -> String {
+    time.with_timezone(&Local).format("%Y-%m-%d").to_string()
+}
+
+/// Cut `start..end` at local midnight into one piece per calendar day.
+pub fn split_at_midnight(start: Timestamp, end: Timestamp) -> Vec<(NaiveDate, Timestamp, Timestamp)> {
+    let mut pieces = Vec::new();
+    let mut piece_start = start;
+    while piece_start < end {
+        let date = piece_start.with_timezone(&Local).date_naive();
+        let midnight = date
+            .succ_opt()
+            .map(|next| local_timestamp(next, NaiveTime::MIN))
+            .unwrap_or(end);
+        let piece_end = midnight.min(end);
+        pieces.push((date, piece_start, piece_end));
+        piece_start = piece_end;
+    }
+    pieces
+}
+
+/// When the day's target is reached if the user started at `start` and takes
+/// `taken` minutes of breaks, plus whatever the break rules still require.
+pub fn calc_endtime(start: Timestamp, schedule: &Schedule, rules: &BreakRules, taken: i64) -> Timestamp {
+    let date = start.with_timezone(&Local).date_naive();
+    let work = rules.work_for_target(schedule.target_minutes(date), taken);
+    start + Duration::minutes(work + taken)
+}
+
+/// "7:05" for 425 minutes; negative values keep their sign.
+pub fn format_minutes(minutes: i64) -> String {
+    let sign = if minutes < 0 { "-" } else { "" };
+    let minutes = minutes.abs();
+    format!("{}{}:{:02}", sign, minutes / 60, minutes % 60)
 }
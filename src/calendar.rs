@@ -0,0 +1,815 @@
+use crate::calc_time::local_timestamp;
+use crate::config::expand_home;
+use crate::model::Timestamp;
+use anyhow::{Context, Result};
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, TimeZone,
+    Utc, Weekday,
+};
+use chrono_tz::Tz;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// One occurrence of a calendar event on a given day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    pub summary: String,
+    /// `None` for all-day events.
+    pub start: Option<Timestamp>,
+    pub end: Option<Timestamp>,
+}
+
+impl CalendarEvent {
+    pub fn is_all_day(&self) -> bool {
+        self.start.is_none()
+    }
+
+    /// "09:00-09:30" or "all day".
+    pub fn time_range(&self) -> String {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) => format!("{}-{}", start.format("%H:%M"), end.format("%H:%M")),
+            (Some(start), None) => start.format("%H:%M").to_string(),
+            _ => "all day".to_string(),
+        }
+    }
+}
+
+/// Events read from one or more `.ics` files.
+///
+/// Times with a `TZID` are converted to local time, by the zone's name or else
+/// by the file's `VTIMEZONE`; events in a zone that is neither known by name
+/// nor defined in the file are left out rather than placed at wrong times.
+/// UTC times are converted too, times without a zone are taken as local time.
+/// Recurrence supports `FREQ` (daily to yearly), `INTERVAL`,
+/// `COUNT`, `UNTIL`, `BYDAY` (with ordinals for monthly rules), `BYMONTHDAY`,
+/// `EXDATE` and moved or cancelled instances via `RECURRENCE-ID`.
+#[derive(Debug, Clone, Default)]
+pub struct Calendar {
+    events: Vec<VEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum EventTime {
+    Date(NaiveDate),
+    /// The wall-clock time in the zone, converted per occurrence, so a
+    /// recurring event keeps its time across daylight saving changes.
+    DateTime(NaiveDateTime, Zone),
+}
+
+impl EventTime {
+    fn date(&self) -> NaiveDate {
+        match self {
+            EventTime::Date(date) => *date,
+            EventTime::DateTime(time, _) => time.date(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Zone {
+    /// No zone given: the same wall-clock time wherever the calendar is read.
+    Floating,
+    Utc,
+    Named(Tz),
+    /// A zone the file defines itself, such as Outlook's
+    /// "W. Europe Standard Time".
+    Defined(VTimezone),
+}
+
+impl Zone {
+    /// The zone named by a `TZID`, if it is known or defined in `zones`.
+    fn of(tzid: &str, zones: &[VTimezone]) -> Option<Zone> {
+        let tzid = tzid.trim_matches('"');
+        if let Ok(tz) = tzid.trim_start_matches('/').parse::<Tz>() {
+            return Some(Zone::Named(tz));
+        }
+        zones.iter().find(|zone| zone.id == tzid).cloned().map(Zone::Defined)
+    }
+
+    /// `time` in this zone as local wall-clock time. A time skipped by a
+    /// daylight saving change counts as the hour after.
+    fn to_local(&self, time: NaiveDateTime) -> Option<NaiveDateTime> {
+        let utc = match self {
+            Zone::Floating => return Some(time),
+            Zone::Utc => time,
+            Zone::Named(tz) => tz
+                .from_local_datetime(&time)
+                .earliest()
+                .or_else(|| tz.from_local_datetime(&(time + Duration::hours(1))).earliest())?
+                .naive_utc(),
+            Zone::Defined(zone) => time - Duration::seconds(i64::from(zone.offset_at(time)?.local_minus_utc())),
+        };
+        Some(DateTime::<Utc>::from_naive_utc_and_offset(utc, Utc).with_timezone(&Local).naive_local())
+    }
+}
+
+/// A `VTIMEZONE`: the offsets its standard and daylight observances switch
+/// to, and when.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct VTimezone {
+    id: String,
+    onsets: Vec<Onset>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Onset {
+    start: NaiveDateTime,
+    offset: FixedOffset,
+    rule: Option<RRule>,
+}
+
+impl VTimezone {
+    /// The offset of the observance that took effect last before `time`.
+    fn offset_at(&self, time: NaiveDateTime) -> Option<FixedOffset> {
+        self.onsets
+            .iter()
+            .filter_map(|onset| Some((onset.last_before(time)?, onset.offset)))
+            .max_by_key(|(since, _)| *since)
+            .or_else(|| self.onsets.first().map(|onset| (onset.start, onset.offset)))
+            .map(|(_, offset)| offset)
+    }
+}
+
+impl Onset {
+    // Observances recur yearly, as in "BYMONTH=3;BYDAY=-1SU"
+    fn last_before(&self, time: NaiveDateTime) -> Option<NaiveDateTime> {
+        let Some(rule) = &self.rule else {
+            return (self.start <= time).then_some(self.start);
+        };
+        let month = rule.by_month.unwrap_or(self.start.month());
+        [time.year(), time.year() - 1].into_iter().find_map(|year| {
+            let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+            let day = match rule.by_day.first() {
+                Some((ordinal, weekday)) => weekdays_in_month(first, *weekday, *ordinal).first().copied()?,
+                None => first.with_day(self.start.day())?,
+            };
+            let since = day.and_time(self.start.time());
+            (self.start <= since && since <= time && rule.until.is_none_or(|until| day <= until)).then_some(since)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct RRule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<NaiveDate>,
+    /// (ordinal, weekday); the ordinal only matters for monthly rules.
+    by_day: Vec<(Option<i32>, Weekday)>,
+    by_month_day: Option<u32>,
+    /// Only read for the observances of a `VTIMEZONE`.
+    by_month: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct VEvent {
+    uid: String,
+    summary: String,
+    start: Option<EventTime>,
+    end: Option<EventTime>,
+    duration: Option<Duration>,
+    rrule: Option<RRule>,
+    exdates: Vec<NaiveDate>,
+    recurrence_id: Option<NaiveDate>,
+    cancelled: bool,
+}
+
+impl VEvent {
+    fn length(&self, start: &EventTime) -> Duration {
+        if let Some(duration) = self.duration {
+            return duration;
+        }
+        match (start, &self.end) {
+            (EventTime::Date(start), Some(EventTime::Date(end))) => *end - *start,
+            (EventTime::DateTime(start, start_zone), Some(EventTime::DateTime(end, end_zone))) => {
+                match (start_zone.to_local(*start), end_zone.to_local(*end)) {
+                    (Some(start), Some(end)) => end - start,
+                    _ => *end - *start,
+                }
+            }
+            (EventTime::Date(_), _) => Duration::days(1),
+            (EventTime::DateTime(..), _) => Duration::zero(),
+        }
+    }
+}
+
+impl Calendar {
+    pub fn parse(text: &str) -> Result<Self> {
+        let lines = unfold(text);
+        let zones = timezones(&lines);
+        let mut events = Vec::new();
+        let mut current: Option<VEvent> = None;
+        for line in &lines {
+            let Some((name, params, value)) = split_property(line) else {
+                continue;
+            };
+            match (name.as_str(), value) {
+                ("BEGIN", "VEVENT") => current = Some(VEvent::default()),
+                ("END", "VEVENT") => events.extend(current.take()),
+                _ => {}
+            }
+            let Some(event) = current.as_mut() else {
+                continue;
+            };
+            match name.as_str() {
+                "UID" => event.uid = value.to_string(),
+                "SUMMARY" => event.summary = unescape(value),
+                "DTSTART" => event.start = parse_time(&params, value, &zones),
+                "DTEND" => event.end = parse_time(&params, value, &zones),
+                "DURATION" => event.duration = parse_duration(value),
+                "RRULE" => event.rrule = parse_rrule(value),
+                "EXDATE" => event
+                    .exdates
+                    .extend(value.split(',').filter_map(|v| parse_time(&params, v, &zones)).map(|t| t.date())),
+                "RECURRENCE-ID" => event.recurrence_id = parse_time(&params, value, &zones).map(|t| t.date()),
+                "STATUS" => event.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+                _ => {}
+            }
+        }
+        Ok(Self {
+            events: events.into_iter().filter(|e| e.start.is_some()).collect(),
+        })
+    }
+
+    /// Read every `.ics` file in `paths`; a directory contributes the `.ics`
+    /// files directly inside it. A leading `~/` stands for the home directory.
+    pub fn load(paths: &[String]) -> Result<Self> {
+        let mut calendar = Calendar::default();
+        for path in paths.iter().map(|p| expand_home(p)) {
+            let files = if path.is_dir() {
+                let mut files: Vec<PathBuf> = std::fs::read_dir(&path)?
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|p| p.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("ics")))
+                    .collect();
+                files.sort();
+                files
+            } else {
+                vec![path]
+            };
+            for file in files {
+                let text = std::fs::read_to_string(&file)
+                    .with_context(|| format!("cannot read calendar {}", file.display()))?;
+                calendar.events.extend(Self::parse(&text)?.events);
+            }
+        }
+        Ok(calendar)
+    }
+
+    /// The events on `date`, all-day events first, the others by start.
+    pub fn events_on(&self, date: NaiveDate) -> Vec<CalendarEvent> {
+        // instances that were moved or cancelled, by (uid, original date)
+        let overridden: HashSet<(&str, NaiveDate)> = self
+            .events
+            .iter()
+            .filter_map(|e| Some((e.uid.as_str(), e.recurrence_id?)))
+            .collect();
+
+        let mut found = Vec::new();
+        for event in self.events.iter().filter(|e| !e.cancelled) {
+            let Some(start) = &event.start else {
+                continue;
+            };
+            let length = event.length(start);
+            // the starts that can still reach into `date`; one in another zone
+            // may fall on the day before or after in local time
+            let (earliest, latest) = match start {
+                EventTime::Date(_) => (date - Duration::days((length.num_days() - 1).max(0)), date),
+                EventTime::DateTime(..) => (date - Duration::days(1), date + Duration::days(1)),
+            };
+            let starts = match (&event.rrule, event.recurrence_id) {
+                (Some(rule), None) => occurrences(start.date(), rule, latest)
+                    .into_iter()
+                    .filter(|d| !event.exdates.contains(d))
+                    .filter(|d| !overridden.contains(&(event.uid.as_str(), *d)))
+                    .collect(),
+                _ => vec![start.date()],
+            };
+            for day in starts.into_iter().filter(|d| (earliest..=latest).contains(d)) {
+                match start {
+                    EventTime::Date(_) => found.push(CalendarEvent {
+                        summary: event.summary.clone(),
+                        start: None,
+                        end: None,
+                    }),
+                    EventTime::DateTime(time, zone) => {
+                        let Some(begin) = zone.to_local(day.and_time(time.time())).filter(|b| b.date() == date) else {
+                            continue;
+                        };
+                        let finish = begin + length;
+                        found.push(CalendarEvent {
+                            summary: event.summary.clone(),
+                            start: Some(local_timestamp(begin.date(), begin.time())),
+                            end: Some(local_timestamp(finish.date(), finish.time())),
+                        });
+                    }
+                }
+            }
+        }
+        found.sort_by_key(|e| e.start);
+        found
+    }
+}
+
+/// Start dates of a recurring event from `start` up to `until` (inclusive).
+fn occurrences(start: NaiveDate, rule: &RRule, until: NaiveDate) -> Vec<NaiveDate> {
+    let last = rule.until.map_or(until, |u| u.min(until));
+    let interval = rule.interval.max(1);
+    let mut dates = Vec::new();
+    let mut emitted = 0;
+    // each step is one period (day, week, month or year) of the rule
+    for step in 0.. {
+        let offset = step * interval;
+        let Some(period_start) = (match rule.frequency {
+            Frequency::Daily => start.checked_add_signed(Duration::days(i64::from(offset))),
+            Frequency::Weekly => start
+                .checked_sub_signed(Duration::days(i64::from(start.weekday().num_days_from_monday())))
+                .and_then(|monday| monday.checked_add_signed(Duration::weeks(i64::from(offset)))),
+            Frequency::Monthly => start.with_day(1).and_then(|d| d.checked_add_months(Months::new(offset))),
+            Frequency::Yearly => start.with_day(1).and_then(|d| d.checked_add_months(Months::new(offset * 12))),
+        }) else {
+            break;
+        };
+        if period_start > last {
+            break;
+        }
+        let mut candidates: Vec<NaiveDate> = match rule.frequency {
+            Frequency::Daily => vec![period_start],
+            Frequency::Weekly if rule.by_day.is_empty() => {
+                vec![period_start + Duration::days(i64::from(start.weekday().num_days_from_monday()))]
+            }
+            Frequency::Weekly => rule
+                .by_day
+                .iter()
+                .map(|(_, w)| period_start + Duration::days(i64::from(w.num_days_from_monday())))
+                .collect(),
+            Frequency::Monthly if !rule.by_day.is_empty() => rule
+                .by_day
+                .iter()
+                .flat_map(|(ordinal, weekday)| weekdays_in_month(period_start, *weekday, *ordinal))
+                .collect(),
+            Frequency::Monthly => period_start
+                .with_day(rule.by_month_day.unwrap_or(start.day()))
+                .into_iter()
+                .collect(),
+            Frequency::Yearly => period_start.with_day(start.day()).into_iter().collect(),
+        };
+        if rule.frequency == Frequency::Daily && !rule.by_day.is_empty() {
+            candidates.retain(|d| rule.by_day.iter().any(|(_, w)| *w == d.weekday()));
+        }
+        candidates.sort();
+        for date in candidates.into_iter().filter(|d| *d >= start) {
+            if date > last || rule.count.is_some_and(|count| emitted >= count) {
+                return dates;
+            }
+            emitted += 1;
+            dates.push(date);
+        }
+    }
+    dates
+}
+
+// All `weekday`s of the month of `first`, or only the `ordinal`th one
+// (negative counts from the end).
+fn weekdays_in_month(first: NaiveDate, weekday: Weekday, ordinal: Option<i32>) -> Vec<NaiveDate> {
+    let all: Vec<NaiveDate> = first
+        .iter_days()
+        .take_while(|d| d.month() == first.month())
+        .filter(|d| d.weekday() == weekday)
+        .collect();
+    match ordinal {
+        None => all,
+        Some(n) if n > 0 => all.get(n as usize - 1).copied().into_iter().collect(),
+        Some(n) => all.len().checked_sub(n.unsigned_abs() as usize).and_then(|i| all.get(i)).copied().into_iter().collect(),
+    }
+}
+
+// The `VTIMEZONE`s of a file, wherever they stand in it.
+fn timezones(lines: &[String]) -> Vec<VTimezone> {
+    let mut zones = Vec::new();
+    let mut current: Option<VTimezone> = None;
+    let (mut start, mut offset, mut rule) = (None, None, None);
+    for line in lines {
+        let Some((name, _, value)) = split_property(line) else {
+            continue;
+        };
+        match (name.as_str(), value) {
+            ("BEGIN", "VTIMEZONE") => current = Some(VTimezone::default()),
+            _ if current.is_none() => {}
+            ("END", "VTIMEZONE") => zones.extend(current.take()),
+            ("BEGIN", "STANDARD" | "DAYLIGHT") => (start, offset, rule) = (None, None, None),
+            ("END", "STANDARD" | "DAYLIGHT") => {
+                if let (Some(zone), Some(start), Some(offset)) = (current.as_mut(), start, offset) {
+                    zone.onsets.push(Onset { start, offset, rule: rule.take() });
+                }
+            }
+            ("TZID", id) => current.iter_mut().for_each(|zone| zone.id = id.to_string()),
+            ("DTSTART", time) => start = NaiveDateTime::parse_from_str(time.trim(), "%Y%m%dT%H%M%S").ok(),
+            ("TZOFFSETTO", to) => offset = parse_offset(to),
+            ("RRULE", value) => rule = parse_rrule(value),
+            _ => {}
+        }
+    }
+    zones
+}
+
+// Continuation lines start with a space or a tab.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+// "NAME;PARAM=x:value" -> ("NAME", ["PARAM=x"], "value")
+fn split_property(line: &str) -> Option<(String, Vec<String>, &str)> {
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ':' if !in_quotes => Some(i),
+        _ => None,
+    })?;
+    let mut head = line[..colon].split(';');
+    let name = head.next()?.to_ascii_uppercase();
+    // parameter names are case-insensitive, a TZID is not
+    let params = head
+        .map(|p| match p.split_once('=') {
+            Some((key, value)) => format!("{}={}", key.to_ascii_uppercase(), value),
+            None => p.to_ascii_uppercase(),
+        })
+        .collect();
+    Some((name, params, &line[colon + 1..]))
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push(' '),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+// `None` for a time in a zone that is neither known nor defined in `zones`
+fn parse_time(params: &[String], value: &str, zones: &[VTimezone]) -> Option<EventTime> {
+    let value = value.trim();
+    if params.iter().any(|p| p.eq_ignore_ascii_case("VALUE=DATE")) || value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d").ok().map(EventTime::Date);
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(EventTime::DateTime(time, Zone::Utc));
+    }
+    let zone = match params.iter().find_map(|p| p.strip_prefix("TZID=")) {
+        Some(tzid) => Zone::of(tzid, zones)?,
+        None => Zone::Floating,
+    };
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Some(EventTime::DateTime(time, zone))
+}
+
+// "+0100", "-0500" or "+053000"
+fn parse_offset(value: &str) -> Option<FixedOffset> {
+    let value = value.trim();
+    let sign = match value.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let hours: i32 = value.get(1..3)?.parse().ok()?;
+    let minutes: i32 = value.get(3..5)?.parse().ok()?;
+    let seconds: i32 = value.get(5..7).map_or(Some(0), |s| s.parse().ok())?;
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60 + seconds))
+}
+
+// "P1D", "PT1H30M", "P1W"; negative durations are not used for events
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim().trim_start_matches('+').strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match unit {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    'S' => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(total)
+}
+
+fn parse_rrule(value: &str) -> Option<RRule> {
+    let mut rule = RRule {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+        by_month_day: None,
+        by_month: None,
+    };
+    let mut frequency = None;
+    for part in value.split(';') {
+        let (key, val) = part.split_once('=')?;
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                frequency = Some(match val.to_ascii_uppercase().as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    _ => return None,
+                })
+            }
+            "INTERVAL" => rule.interval = val.parse().ok()?,
+            "COUNT" => rule.count = val.parse().ok(),
+            "UNTIL" => {
+                rule.until = parse_time(&[], val, &[]).map(|t| match t {
+                    EventTime::DateTime(time, zone) => zone.to_local(time).unwrap_or(time).date(),
+                    date => date.date(),
+                })
+            }
+            "BYDAY" => {
+                rule.by_day = val
+                    .split(',')
+                    .filter_map(|day| {
+                        let split = day.len().checked_sub(2)?;
+                        let weekday = match day.get(split..)? {
+                            "MO" => Weekday::Mon,
+                            "TU" => Weekday::Tue,
+                            "WE" => Weekday::Wed,
+                            "TH" => Weekday::Thu,
+                            "FR" => Weekday::Fri,
+                            "SA" => Weekday::Sat,
+                            "SU" => Weekday::Sun,
+                            _ => return None,
+                        };
+                        let ordinal = day.get(..split)?.parse().ok();
+                        Some((ordinal, weekday))
+                    })
+                    .collect()
+            }
+            "BYMONTHDAY" => rule.by_month_day = val.split(',').next()?.parse().ok(),
+            "BYMONTH" => rule.by_month = val.split(',').next()?.parse().ok(),
+            _ => {}
+        }
+    }
+    rule.frequency = frequency?;
+    Some(rule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calendar(body: &str) -> Calendar {
+        Calendar::parse(&format!("BEGIN:VCALENDAR\nVERSION:2.0\n{}\nEND:VCALENDAR\n", body.trim())).unwrap()
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    /// "summary hh:mm-hh:mm" for each event on `day`.
+    fn on(calendar: &Calendar, day: &str) -> Vec<String> {
+        calendar
+            .events_on(date(day))
+            .iter()
+            .map(|e| format!("{} {}", e.summary, e.time_range()))
+            .collect()
+    }
+
+    const WEEKLY: &str = "
+BEGIN:VEVENT
+UID:jf
+SUMMARY:Jour fixe
+DTSTART:20240603T090000
+DTEND:20240603T093000
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE
+END:VEVENT";
+
+    #[test]
+    fn weekly_rule_repeats_on_its_days() {
+        let calendar = calendar(WEEKLY);
+        assert_eq!(on(&calendar, "2024-06-03"), ["Jour fixe 09:00-09:30"]);
+        assert_eq!(on(&calendar, "2024-06-05"), ["Jour fixe 09:00-09:30"]);
+        assert_eq!(on(&calendar, "2024-06-24"), ["Jour fixe 09:00-09:30"]);
+        assert!(on(&calendar, "2024-06-04").is_empty());
+        // nothing before the start
+        assert!(on(&calendar, "2024-05-29").is_empty());
+    }
+
+    #[test]
+    fn interval_skips_periods() {
+        let calendar = calendar(&WEEKLY.replace("FREQ=WEEKLY", "FREQ=WEEKLY;INTERVAL=2"));
+        assert_eq!(on(&calendar, "2024-06-17"), ["Jour fixe 09:00-09:30"]);
+        assert!(on(&calendar, "2024-06-10").is_empty());
+    }
+
+    #[test]
+    fn count_limits_the_occurrences() {
+        // the first one is on the start, a Monday
+        let calendar = calendar(&WEEKLY.replace("BYDAY=MO,WE", "BYDAY=MO,WE;COUNT=3"));
+        assert_eq!(on(&calendar, "2024-06-10"), ["Jour fixe 09:00-09:30"]);
+        assert!(on(&calendar, "2024-06-12").is_empty());
+    }
+
+    #[test]
+    fn until_includes_its_day() {
+        let calendar = calendar(&WEEKLY.replace("BYDAY=MO,WE", "BYDAY=MO,WE;UNTIL=20240612"));
+        assert_eq!(on(&calendar, "2024-06-12"), ["Jour fixe 09:00-09:30"]);
+        assert!(on(&calendar, "2024-06-17").is_empty());
+    }
+
+    #[test]
+    fn exdate_drops_an_occurrence() {
+        let calendar = calendar(&WEEKLY.replace("RRULE:", "EXDATE:20240605T090000,20240610T090000\nRRULE:"));
+        assert!(on(&calendar, "2024-06-05").is_empty());
+        assert!(on(&calendar, "2024-06-10").is_empty());
+        assert_eq!(on(&calendar, "2024-06-12"), ["Jour fixe 09:00-09:30"]);
+    }
+
+    #[test]
+    fn overridden_instances_move_or_vanish() {
+        let body = format!(
+            "{}
+BEGIN:VEVENT
+UID:jf
+RECURRENCE-ID:20240610T090000
+SUMMARY:Jour fixe (moved)
+DTSTART:20240611T140000
+DTEND:20240611T150000
+END:VEVENT
+BEGIN:VEVENT
+UID:jf
+RECURRENCE-ID:20240612T090000
+STATUS:CANCELLED
+SUMMARY:Jour fixe
+DTSTART:20240612T090000
+END:VEVENT",
+            WEEKLY
+        );
+        let calendar = calendar(&body);
+        assert!(on(&calendar, "2024-06-10").is_empty());
+        assert_eq!(on(&calendar, "2024-06-11"), ["Jour fixe (moved) 14:00-15:00"]);
+        assert!(on(&calendar, "2024-06-12").is_empty());
+        assert_eq!(on(&calendar, "2024-06-17"), ["Jour fixe 09:00-09:30"]);
+    }
+
+    #[test]
+    fn monthly_rule_with_an_ordinal_weekday() {
+        let calendar = calendar(
+            "
+BEGIN:VEVENT
+UID:review
+SUMMARY:Review
+DTSTART:20240628T150000
+DURATION:PT1H
+RRULE:FREQ=MONTHLY;BYDAY=-1FR
+END:VEVENT",
+        );
+        assert_eq!(on(&calendar, "2024-07-26"), ["Review 15:00-16:00"]);
+        assert!(on(&calendar, "2024-07-19").is_empty());
+    }
+
+    #[test]
+    fn all_day_events_come_first() {
+        let calendar = calendar(&format!(
+            "{}
+BEGIN:VEVENT
+UID:off
+SUMMARY:Offsite
+DTSTART;VALUE=DATE:20240603
+DTEND;VALUE=DATE:20240605
+END:VEVENT",
+            WEEKLY
+        ));
+        assert_eq!(on(&calendar, "2024-06-03"), ["Offsite all day", "Jour fixe 09:00-09:30"]);
+        assert_eq!(on(&calendar, "2024-06-04"), ["Offsite all day"]);
+        assert!(on(&calendar, "2024-06-05").iter().all(|e| !e.starts_with("Offsite")));
+    }
+
+    #[test]
+    fn odd_weekdays_are_ignored() {
+        assert_eq!(parse_rrule("FREQ=WEEKLY;BYDAY=MÖ,TU").unwrap().by_day, [(None, Weekday::Tue)]);
+    }
+
+    // The local start and end of an event from 09:00 to 10:00 in `tz`.
+    fn expected(tz: Tz, day: &str) -> (Timestamp, Timestamp) {
+        let start = tz
+            .from_local_datetime(&date(day).and_hms_opt(9, 0, 0).unwrap())
+            .unwrap()
+            .with_timezone(&Local)
+            .fixed_offset();
+        (start, start + Duration::hours(1))
+    }
+
+    fn times(calendar: &Calendar, day: NaiveDate) -> Vec<(Timestamp, Timestamp)> {
+        calendar.events_on(day).iter().map(|e| (e.start.unwrap(), e.end.unwrap())).collect()
+    }
+
+    const BERLIN: &str = "
+BEGIN:VTIMEZONE
+TZID:W. Europe Standard Time
+BEGIN:STANDARD
+DTSTART:16010101T030000
+TZOFFSETFROM:+0200
+TZOFFSETTO:+0100
+RRULE:FREQ=YEARLY;BYDAY=-1SU;BYMONTH=10
+END:STANDARD
+BEGIN:DAYLIGHT
+DTSTART:16010101T020000
+TZOFFSETFROM:+0100
+TZOFFSETTO:+0200
+RRULE:FREQ=YEARLY;BYDAY=-1SU;BYMONTH=3
+END:DAYLIGHT
+END:VTIMEZONE";
+
+    #[test]
+    fn vtimezone_and_iana_tzid_agree() {
+        let event = |tzid: &str| {
+            format!(
+                "BEGIN:VEVENT
+UID:{0}
+SUMMARY:Sync
+DTSTART;TZID={0}:20240301T090000
+DTEND;TZID={0}:20240301T100000
+RRULE:FREQ=MONTHLY
+END:VEVENT",
+                tzid
+            )
+        };
+        let defined = calendar(&format!("{}\n{}", BERLIN, event("W. Europe Standard Time")));
+        let named = calendar(&event("Europe/Berlin"));
+        // before and after the change to summer time
+        for day in ["2024-03-01", "2024-04-01", "2024-11-01"] {
+            let (start, end) = expected(chrono_tz::Europe::Berlin, day);
+            let local = start.with_timezone(&Local).date_naive();
+            assert_eq!(times(&defined, local), [(start, end)], "{}", day);
+            assert_eq!(times(&named, local), [(start, end)], "{}", day);
+        }
+    }
+
+    #[test]
+    fn iana_tzid_converts_to_local_time() {
+        let calendar = calendar(
+            "
+BEGIN:VEVENT
+UID:ny
+SUMMARY:Standup
+DTSTART;TZID=America/New_York:20240610T090000
+DTEND;TZID=America/New_York:20240610T100000
+END:VEVENT",
+        );
+        let (start, end) = expected(chrono_tz::America::New_York, "2024-06-10");
+        assert_eq!(times(&calendar, start.with_timezone(&Local).date_naive()), [(start, end)]);
+    }
+
+    #[test]
+    fn unknown_zones_are_left_out() {
+        let calendar = calendar(
+            "
+BEGIN:VEVENT
+UID:x
+SUMMARY:Somewhere
+DTSTART;TZID=Nowhere Standard Time:20240610T090000
+END:VEVENT",
+        );
+        for day in ["2024-06-09", "2024-06-10", "2024-06-11"] {
+            assert!(on(&calendar, day).is_empty());
+        }
+    }
+}
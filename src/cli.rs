@@ -0,0 +1,800 @@
+use crate::app::App;
+use crate::balance::Balance;
+use crate::calendar::Calendar;
+use crate::calc_time::{format_minutes, now};
+use crate::config::{Config, CsvLayout, CsvUnit, NotesLayout};
+use crate::control::{self, Request, Response, DEFAULT_SOCKET_PATH};
+use crate::interop::{
+    days_from_intervals, intervals_from_day, read_timewarrior, read_toggl, store_days, write_timewarrior, write_toggl,
+    Interval,
+};
+use crate::export_notes::write_notes;
+use crate::journal::Journal;
+use crate::model::ActionItem;
+use crate::read_json::read_json;
+use crate::invoice::Invoice;
+use crate::report::{Period, Report};
+use crate::status::Status;
+use crate::tabs::BalanceTab;
+use crate::timesheet::{default_timesheet_path, write_timesheet};
+use crate::store::{migrate_jsonl_to_sqlite, open_default, DEFAULT_JSONL_PATH, DEFAULT_SQLITE_PATH};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{Datelike, Local, NaiveDate};
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: time_tracking_basic [COMMAND]
+
+Without a command the TUI starts.
+
+Commands:
+  start [HH:MM]            start the day (default: now)
+  stop [HH:MM]             end the day and save it to the history (default: now)
+  meeting start [--project P] [--non-billable] <name>
+                           start a meeting, booked to P or the running project
+  meeting stop             end the running meeting
+  meeting pause|resume     interrupt the running meeting; paused time does
+                           not count
+  focus start|stop|toggle  start or end a focus session
+  focus start --non-billable
+                           start a focus session that is not billed
+  focus pause|resume       interrupt the running focus session
+  billable on|off          mark the running meeting and focus session as
+                           billable or not
+  break start|stop         start or end a break
+  project start <project>[/<task>] [--task T]
+                           book the time from now on to a project
+  project stop             stop booking time to the running project
+  project list             show the projects of the config
+  calendar [DATE] [--file PATH]
+                           list the calendar events of DATE (default: today)
+  calendar import [--file PATH]
+                           add today's calendar events to the meetings
+  actions [SEARCH] [--all]
+                           list the open action items of all meetings, or with
+                           --all the done ones too; SEARCH matches the text,
+                           owner and meeting
+  status [--json | --format FMT]
+                           show the running day; FMT may use {start} {worked}
+                           {worked_minutes} {target} {left} {end} {balance}
+                           {today} {meeting} {meeting_minutes} {focus}
+                           {focus_minutes} {break} {project} {project_minutes}
+  report [DAYS]            worked time of the last DAYS stored days (default: 7)
+  report week|month [DATE] [--markdown | --html] [--output PATH]
+                           report of the week or month containing DATE
+                           (default: today; a month also takes YYYY-MM)
+  invoice CLIENT week|month [DATE] [--markdown | --html | --json] [--output PATH]
+                           invoice draft of the client's billable project
+                           time in the week or month containing DATE
+  timesheet [YYYY-MM] [--output PATH]
+                           printable PDF timesheet of a month (default: this
+                           month) to data/timesheets
+  export csv [FROM [TO]] [--days | --sessions] [--minutes | --hours]
+             [--delimiter C] [--output PATH]
+                           write the stored days from FROM to TO (YYYY-MM-DD,
+                           default: this month) as CSV, to stdout by default
+  export ics [FROM [TO]] [--output PATH]
+                           write the work blocks, meetings and focus sessions
+                           from FROM to TO as iCalendar, to stdout by default
+  export notes [FROM [TO]] [--per-meeting | --per-day] [--dir PATH]
+                           write the meeting notes from FROM to TO as Markdown
+                           files with front matter (default: [notes] dir)
+  export timewarrior|toggl [FROM [TO]] [--output PATH]
+                           write the days from FROM to TO as Timewarrior JSON
+                           (for `timew import`) or as Toggl CSV
+  import timewarrior PATH [--replace]
+                           import a `timew export` file, a .data file or the
+                           Timewarrior data directory
+  import toggl FILE [--replace]
+                           import a Toggl CSV export; days already stored are
+                           kept unless --replace is given
+  migrate [PATH]           import a JSONL history into SQLite
+  help                     show this help";
+
+/// Run the subcommand in `args` (the arguments after the program name). If
+/// the TUI is running, commands go to it over its control socket; otherwise
+/// they work on the journal of the in-progress day, the same one the TUI
+/// writes, so both can be used on the same day.
+pub fn run(args: &[String], config: Config) -> Result<()> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["start"] => print(send(config, Request::SetStart { time: clock_or_now(None) })?),
+        ["start", time] => print(send(config, Request::SetStart { time: time.to_string() })?),
+        ["stop"] => stop(config, clock_or_now(None)),
+        ["stop", time] => stop(config, time.to_string()),
+        ["meeting", "start", words @ ..] if !words.is_empty() => {
+            let request = meeting_request(words)?;
+            print(send(config, request)?)
+        }
+        ["meeting", "stop"] => print(send(config, Request::MeetingStop)?),
+        ["meeting", "pause"] => print(send(config, Request::MeetingPause)?),
+        ["meeting", "resume"] => print(send(config, Request::MeetingResume)?),
+        ["focus", "start"] => print(send(config, Request::FocusStart { billable: true })?),
+        ["focus", "start", "--non-billable"] => print(send(config, Request::FocusStart { billable: false })?),
+        ["focus", "stop"] => print(send(config, Request::FocusStop)?),
+        ["focus", "toggle"] => print(send(config, Request::FocusToggle)?),
+        ["focus", "pause"] => print(send(config, Request::FocusPause)?),
+        ["focus", "resume"] => print(send(config, Request::FocusResume)?),
+        ["break", "start"] => print(send(config, Request::BreakStart)?),
+        ["break", "stop"] => print(send(config, Request::BreakStop)?),
+        ["billable", state @ ("on" | "off")] => print(send(config, Request::SetBillable { billable: *state == "on" })?),
+        ["calendar", "import", options @ ..] => {
+            let paths = calendar_files(options)?
+                .iter()
+                .map(|path| Ok(std::path::absolute(path)?.display().to_string()))
+                .collect::<Result<Vec<String>>>()?;
+            print(send(config, Request::CalendarImport { paths })?)
+        }
+        ["calendar", options @ ..] => list_calendar(config, options),
+        ["project", "start", words @ ..] if !words.is_empty() => {
+            let request = project_request(words)?;
+            print(send(config, request)?)
+        }
+        ["project", "stop"] => print(send(config, Request::ProjectStop)?),
+        ["project", "list"] | ["projects"] => {
+            list_projects(&config);
+            Ok(())
+        }
+        ["actions", options @ ..] => list_action_items(config, options),
+        ["status"] => print_status(&status(config)?),
+        ["status", "--json"] => {
+            println!("{}", serde_json::to_string(&status(config)?)?);
+            Ok(())
+        }
+        ["status", "--format", template] => {
+            println!("{}", status(config)?.format(template));
+            Ok(())
+        }
+        ["report"] => report(config, 7),
+        ["report", kind @ ("week" | "month"), options @ ..] => report_period(config, kind, options),
+        ["report", days] => report(
+            config,
+            days.parse().map_err(|_| anyhow!("{:?} is not a number of days", days))?,
+        ),
+        ["invoice", client, kind @ ("week" | "month"), options @ ..] => invoice(config, client, kind, options),
+        ["timesheet", options @ ..] => timesheet(config, options),
+        ["export", "csv", options @ ..] => export_csv(config, options),
+        ["export", "ics", options @ ..] => export_ics(options),
+        ["export", "notes", options @ ..] => export_notes(config, options),
+        ["export", format @ ("timewarrior" | "toggl"), options @ ..] => export_intervals(config, format, options),
+        ["import", format @ ("timewarrior" | "toggl"), path, options @ ..] => {
+            import_intervals(config, format, path, options)
+        }
+        ["migrate"] => migrate(DEFAULT_JSONL_PATH),
+        ["migrate", path] => migrate(path),
+        ["help"] | ["--help"] | ["-h"] => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => bail!("unknown command {:?}\n\n{}", args.join(" "), USAGE),
+    }
+}
+
+/// The day in progress, replayed from the journal. A journal left over from an
+/// earlier day is saved to the history first, as the TUI offers on startup.
+fn running_day(config: Config) -> Result<App> {
+    let journal = Journal::open_default();
+    let entries = journal.entries()?;
+    let mut app = App::headless(config.clone());
+    if Journal::is_current(&entries) {
+        app.restore_from_journal(&entries, now());
+    } else if let Some(first) = entries.first() {
+        App::export_unfinished(config, &entries)?;
+        eprintln!("Saved the unfinished day {} to the history", first.date);
+    }
+    Ok(app)
+}
+
+/// Hand `request` to the running TUI, or carry it out here without one.
+fn send(config: Config, request: Request) -> Result<Response> {
+    if let Some(response) = control::send(DEFAULT_SOCKET_PATH, &request)? {
+        return Ok(response);
+    }
+    let mut app = running_day(config)?;
+    Ok(control::apply(&mut app, request))
+}
+
+fn print(response: Response) -> Result<()> {
+    if !response.ok {
+        bail!("{}", response.message);
+    }
+    println!("{}", response.message);
+    Ok(())
+}
+
+fn clock_or_now(time: Option<&str>) -> String {
+    time.map(str::to_string)
+        .unwrap_or_else(|| now().format("%H:%M").to_string())
+}
+
+/// Without a TUI the day is saved right away; a running TUI keeps the end
+/// time and saves the day when it quits.
+fn stop(config: Config, time: String) -> Result<()> {
+    let request = Request::SetEnd { time };
+    if let Some(response) = control::send(DEFAULT_SOCKET_PATH, &request)? {
+        print(response)?;
+        println!("The running tracker saves the day when it quits");
+        return Ok(());
+    }
+    let mut app = running_day(config)?;
+    let response = control::apply(&mut app, request);
+    if !response.ok {
+        return print(response);
+    }
+    let end = app.endtime_pairs[&app.endtime_key];
+    app.close_running_sessions(end);
+    app.export_json()?;
+    println!(
+        "Stopped at {}, worked {} of {}",
+        end.format("%H:%M"),
+        format_minutes(app.worked_minutes(end)),
+        format_minutes(app.target_minutes())
+    );
+    Ok(())
+}
+
+fn list_action_items(config: Config, options: &[&str]) -> Result<()> {
+    let mut all = false;
+    let mut words = Vec::new();
+    for option in options {
+        match *option {
+            "--all" => all = true,
+            word => words.push(word),
+        }
+    }
+    let search = words.join(" ").to_lowercase();
+
+    let app = running_day(config)?;
+    let today = app.starttime().format("%Y-%m-%d").to_string();
+    let days = open_default()?.load_all()?;
+    let mut meetings: Vec<(&str, &str, &[ActionItem])> = days
+        .iter()
+        .flat_map(|day| {
+            day.meetings
+                .iter()
+                .map(|m| (day.date.as_str(), m.meeting_name.as_str(), &m.action_items[..]))
+        })
+        .collect();
+    meetings.extend(
+        app.meeting_list
+            .iter()
+            .map(|m| (today.as_str(), m.meeting_name.as_str(), &m.action_items[..])),
+    );
+    if app.meeting_running {
+        meetings.push((today.as_str(), app.meeting_name.as_str(), &app.meeting_action_items[..]));
+    }
+
+    let mut items: Vec<(&str, &str, &ActionItem)> = meetings
+        .into_iter()
+        .flat_map(|(date, meeting, items)| items.iter().map(move |item| (date, meeting, item)))
+        .filter(|(_, _, item)| all || !item.done)
+        .filter(|(_, meeting, item)| {
+            search.is_empty() || item.matches(&search) || meeting.to_lowercase().contains(&search)
+        })
+        .collect();
+    if items.is_empty() {
+        println!("No {}action items", if all { "" } else { "open " });
+        return Ok(());
+    }
+    items.sort_by_key(|(date, _, item)| (item.done, item.due.is_none(), item.due, *date));
+    let local_today = Local::now().date_naive();
+    for (date, meeting, item) in items {
+        println!(
+            "[{}] {}  ({} {}){}",
+            if item.done { "x" } else { " " },
+            item.describe(),
+            date,
+            meeting,
+            if item.is_overdue(local_today) { "  OVERDUE" } else { "" }
+        );
+    }
+    Ok(())
+}
+
+/// Without a TUI the balance is read from the history here. Unlike the other
+/// commands, `status` leaves a journal from an earlier day where it is and
+/// only reports it.
+fn status(config: Config) -> Result<Status> {
+    if let Some(response) = control::send(DEFAULT_SOCKET_PATH, &Request::Status)? {
+        return response
+            .status
+            .ok_or_else(|| anyhow!("no status in the answer: {}", response.message));
+    }
+    let entries = Journal::open_default().entries()?;
+    let mut app = App::headless(config);
+    let unsaved_day = if Journal::is_current(&entries) {
+        app.restore_from_journal(&entries, now());
+        None
+    } else {
+        entries.first().map(|first| first.date.clone())
+    };
+    app.balance_tab = BalanceTab::with_config(&app.config);
+    let response = control::apply(&mut app, Request::Status);
+    let mut status = response
+        .status
+        .ok_or_else(|| anyhow!("no status in the answer: {}", response.message))?;
+    status.unsaved_day = unsaved_day;
+    Ok(status)
+}
+
+fn print_status(status: &Status) -> Result<()> {
+    if let Some(date) = &status.unsaved_day {
+        println!("The day {} is still in the journal, the next command that records time saves it", date);
+    }
+    if !status.started {
+        println!("No day started, assuming the default start");
+    }
+    println!("Started:   {}", status.starttime.format("%H:%M"));
+    println!(
+        "Worked:    {} of {} (target reached at {})",
+        format_minutes(status.worked_minutes),
+        format_minutes(status.target_minutes),
+        status.predicted_endtime.format("%H:%M")
+    );
+    if let Some(meeting) = &status.meeting {
+        println!(
+            "Meeting:   {} since {} ({} min{}){}",
+            meeting.name,
+            meeting.since.format("%H:%M"),
+            meeting.minutes,
+            paused(meeting.paused),
+            non_billable(meeting.billable)
+        );
+    }
+    if let Some(focus) = &status.focus {
+        println!(
+            "Focus:     since {} ({} min{}){}",
+            focus.since.format("%H:%M"),
+            focus.minutes,
+            paused(focus.paused),
+            non_billable(focus.billable)
+        );
+    }
+    if let Some(pause) = &status.on_break {
+        println!("Break:     since {} ({} min)", pause.since.format("%H:%M"), pause.minutes);
+    }
+    if let Some(project) = &status.project {
+        let task = project.task.as_ref().map(|t| format!(" / {}", t)).unwrap_or_default();
+        println!("Project:   {}{} since {} ({} min)", project.name, task, project.since.format("%H:%M"), project.minutes);
+    }
+    println!(
+        "Balance:   {} (today {})",
+        format_minutes(status.balance),
+        format_minutes(status.balance_today)
+    );
+    Ok(())
+}
+
+fn paused(paused: bool) -> &'static str {
+    if paused {
+        ", paused"
+    } else {
+        ""
+    }
+}
+
+fn non_billable(billable: bool) -> &'static str {
+    if billable {
+        ""
+    } else {
+        ", non-billable"
+    }
+}
+
+// `[--project P] [--non-billable] <name>`, options in any order before the name
+fn meeting_request(words: &[&str]) -> Result<Request> {
+    let mut project = None;
+    let mut billable = true;
+    let mut words = words;
+    loop {
+        match words {
+            ["--project", name, rest @ ..] => {
+                project = Some(name.to_string());
+                words = rest;
+            }
+            ["--non-billable", rest @ ..] => {
+                billable = false;
+                words = rest;
+            }
+            _ => break,
+        }
+    }
+    if words.is_empty() {
+        bail!("meeting start needs a name");
+    }
+    Ok(Request::MeetingStart {
+        name: words.join(" "),
+        project,
+        billable,
+    })
+}
+
+// "ACME/Backend", "ACME --task Backend" or just "ACME"
+fn project_request(words: &[&str]) -> Result<Request> {
+    let (words, task) = match words {
+        [words @ .., "--task", task] => (words, Some(task.to_string())),
+        words => (words, None),
+    };
+    let text = words.join(" ");
+    let (project, task) = match text.split_once('/') {
+        Some((project, inline)) if task.is_none() => (project.trim().to_string(), Some(inline.trim().to_string())),
+        _ => (text.trim().to_string(), task),
+    };
+    if project.is_empty() {
+        bail!("project start needs a project name");
+    }
+    Ok(Request::ProjectStart {
+        project,
+        task: task.filter(|t| !t.is_empty()),
+    })
+}
+
+fn list_projects(config: &Config) {
+    if config.projects.is_empty() {
+        println!("No projects configured, add [[projects]] to the config");
+    }
+    for project in &config.projects {
+        match &project.cost_center {
+            Some(cost_center) => println!("{} (cost center {})", project.name, cost_center),
+            None => println!("{}", project.name),
+        }
+        for task in &project.tasks {
+            println!("  {}", task);
+        }
+    }
+}
+
+fn report(config: Config, days: usize) -> Result<()> {
+    let mut worktime = read_json()?;
+    worktime.sort_by(|a, b| a.date.cmp(&b.date));
+    let today = Local::now().date_naive();
+
+    println!("{:<10}  {:>5}  {:>5}  {:>6}  {:>6}  {:>6}", "Date", "Start", "End", "Worked", "Target", "+/-");
+    for day in worktime.iter().skip(worktime.len().saturating_sub(days)) {
+        let worked = day.worked_minutes(&config.breaks);
+        let target = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d")
+            .map(|date| config.schedule.target_minutes(date))
+            .unwrap_or(0);
+        println!(
+            "{:<10}  {:>5}  {:>5}  {:>6}  {:>6}  {:>6}",
+            day.date,
+            day.starttime.format("%H:%M").to_string(),
+            day.endtime.format("%H:%M").to_string(),
+            format_minutes(worked),
+            format_minutes(target),
+            format_minutes(worked - target)
+        );
+    }
+    let balance = Balance::calculate(&worktime, &config, today);
+    println!("Balance until yesterday: {}", format_minutes(balance.total()));
+    Ok(())
+}
+
+fn report_period(config: Config, kind: &str, options: &[&str]) -> Result<()> {
+    let today = Local::now().date_naive();
+    let mut date = today;
+    let mut html = false;
+    let mut output = None;
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match *option {
+            "--markdown" => html = false,
+            "--html" => html = true,
+            "--output" => {
+                let path = options.next().ok_or_else(|| anyhow!("--output needs a path"))?;
+                output = Some(PathBuf::from(path));
+            }
+            value => {
+                date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                    .or_else(|_| NaiveDate::parse_from_str(&format!("{}-01", value), "%Y-%m-%d"))
+                    .map_err(|_| anyhow!("{:?} is neither an option nor a date", value))?;
+            }
+        }
+    }
+    let period = match kind {
+        "week" => Period::week_of(date),
+        _ => Period::month_of(date),
+    };
+
+    let report = Report::build(&read_json()?, &config, period, today);
+    let text = if html { report.to_html() } else { report.to_markdown() };
+    match output {
+        Some(path) => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, text)?;
+            eprintln!("Wrote {}", path.display());
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+fn invoice(config: Config, client: &str, kind: &str, options: &[&str]) -> Result<()> {
+    let mut date = Local::now().date_naive();
+    let mut format = "markdown";
+    let mut output = None;
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match *option {
+            "--markdown" => format = "markdown",
+            "--html" => format = "html",
+            "--json" => format = "json",
+            "--output" => {
+                let path = options.next().ok_or_else(|| anyhow!("--output needs a path"))?;
+                output = Some(PathBuf::from(path));
+            }
+            value => {
+                date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                    .or_else(|_| NaiveDate::parse_from_str(&format!("{}-01", value), "%Y-%m-%d"))
+                    .map_err(|_| anyhow!("{:?} is neither an option nor a date", value))?;
+            }
+        }
+    }
+    let period = match kind {
+        "week" => Period::week_of(date),
+        _ => Period::month_of(date),
+    };
+
+    let invoice = Invoice::build(&read_json()?, &config, client, period)?;
+    let text = match format {
+        "html" => invoice.to_html(),
+        "json" => invoice.to_json()?,
+        _ => invoice.to_markdown(),
+    };
+    match output {
+        Some(path) => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, text)?;
+            eprintln!("Wrote {}", path.display());
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+// `--file PATH` options, which replace the configured calendars
+fn calendar_files(options: &[&str]) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match *option {
+            "--file" => files.push(options.next().ok_or_else(|| anyhow!("--file needs a path"))?.to_string()),
+            value => bail!("unknown option {:?}", value),
+        }
+    }
+    Ok(files)
+}
+
+fn list_calendar(config: Config, options: &[&str]) -> Result<()> {
+    let mut date = Local::now().date_naive();
+    let mut rest = options;
+    if let Some(first) = options.first().filter(|o| !o.starts_with("--")) {
+        date = NaiveDate::parse_from_str(first, "%Y-%m-%d")
+            .map_err(|_| anyhow!("{:?} is not a YYYY-MM-DD date", first))?;
+        rest = &options[1..];
+    }
+    let mut paths = calendar_files(rest)?;
+    if paths.is_empty() {
+        paths = config.calendar.paths;
+    }
+    if paths.is_empty() {
+        bail!("no calendar configured, set [calendar] paths in the config or pass --file");
+    }
+    let events = Calendar::load(&paths)?.events_on(date);
+    if events.is_empty() {
+        println!("No events on {}", date);
+    }
+    for event in events {
+        println!("{:<11}  {}", event.time_range(), event.summary);
+    }
+    Ok(())
+}
+
+fn timesheet(config: Config, options: &[&str]) -> Result<()> {
+    let mut month = Local::now().date_naive();
+    let mut output = None;
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match *option {
+            "--output" => {
+                let path = options.next().ok_or_else(|| anyhow!("--output needs a path"))?;
+                output = Some(PathBuf::from(path));
+            }
+            value => {
+                month = NaiveDate::parse_from_str(&format!("{}-01", value), "%Y-%m-%d")
+                    .map_err(|_| anyhow!("{:?} is neither an option nor a YYYY-MM month", value))?;
+            }
+        }
+    }
+    let path = output.unwrap_or_else(|| default_timesheet_path(month.year(), month.month()));
+    write_timesheet(&read_json()?, &config, month.year(), month.month(), &path)?;
+    println!("Wrote {}", path.display());
+    Ok(())
+}
+
+fn export_csv(config: Config, options: &[&str]) -> Result<()> {
+    let mut dates = Vec::new();
+    let mut settings = config.csv.clone();
+    let mut output = None;
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match *option {
+            "--days" => settings.layout = CsvLayout::Days,
+            "--sessions" => settings.layout = CsvLayout::Sessions,
+            "--minutes" => settings.unit = CsvUnit::Minutes,
+            "--hours" => settings.unit = CsvUnit::Hours,
+            "--delimiter" => {
+                let value = options.next().ok_or_else(|| anyhow!("--delimiter needs a character"))?;
+                settings.delimiter = match *value {
+                    "tab" | "\\t" => '\t',
+                    value => value
+                        .chars()
+                        .next()
+                        .filter(|c| c.is_ascii() && value.len() == 1)
+                        .ok_or_else(|| anyhow!("{:?} is not a single ASCII character", value))?,
+                };
+            }
+            "--output" => {
+                let path = options.next().ok_or_else(|| anyhow!("--output needs a path"))?;
+                output = Some(PathBuf::from(path));
+            }
+            date => dates.push(parse_export_date(date)?),
+        }
+    }
+    let (from, to) = export_range(&dates, "csv")?;
+
+    match output {
+        Some(path) => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let days = crate::export_csv::export_csv(from, to, &config.breaks, &settings, std::fs::File::create(&path)?)?;
+            eprintln!("Exported {} days to {}", days, path.display());
+        }
+        None => {
+            crate::export_csv::export_csv(from, to, &config.breaks, &settings, std::io::stdout().lock())?;
+        }
+    }
+    Ok(())
+}
+
+fn export_ics(options: &[&str]) -> Result<()> {
+    let mut dates = Vec::new();
+    let mut output = None;
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match *option {
+            "--output" => {
+                let path = options.next().ok_or_else(|| anyhow!("--output needs a path"))?;
+                output = Some(PathBuf::from(path));
+            }
+            date => dates.push(parse_export_date(date)?),
+        }
+    }
+    let (from, to) = export_range(&dates, "ics")?;
+
+    match output {
+        Some(path) => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let days = crate::export_ics::export_ics(from, to, std::fs::File::create(&path)?)?;
+            eprintln!("Exported {} days to {}", days, path.display());
+        }
+        None => {
+            crate::export_ics::export_ics(from, to, std::io::stdout().lock())?;
+        }
+    }
+    Ok(())
+}
+
+fn export_notes(config: Config, options: &[&str]) -> Result<()> {
+    let mut dates = Vec::new();
+    let mut layout = config.notes.layout;
+    let mut dir = config.notes.dir_path();
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match *option {
+            "--per-meeting" => layout = NotesLayout::Meeting,
+            "--per-day" => layout = NotesLayout::Day,
+            "--dir" => {
+                let path = options.next().ok_or_else(|| anyhow!("--dir needs a path"))?;
+                dir = PathBuf::from(path);
+            }
+            date => dates.push(parse_export_date(date)?),
+        }
+    }
+    let (from, to) = export_range(&dates, "notes")?;
+    let mut days = open_default()?.load_range(&from.to_string(), &to.to_string())?;
+    days.sort_by(|a, b| a.date.cmp(&b.date));
+    let paths = write_notes(&days, &dir, layout)?;
+    println!("Wrote {} notes files to {}", paths.len(), dir.display());
+    Ok(())
+}
+
+fn export_intervals(config: Config, format: &str, options: &[&str]) -> Result<()> {
+    let mut dates = Vec::new();
+    let mut output = None;
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match *option {
+            "--output" => {
+                let path = options.next().ok_or_else(|| anyhow!("--output needs a path"))?;
+                output = Some(PathBuf::from(path));
+            }
+            date => dates.push(parse_export_date(date)?),
+        }
+    }
+    let (from, to) = export_range(&dates, format)?;
+    let mut days = open_default()?.load_range(&from.to_string(), &to.to_string())?;
+    days.sort_by(|a, b| a.date.cmp(&b.date));
+    let intervals: Vec<Interval> = days
+        .iter()
+        .flat_map(|day| intervals_from_day(day, &config.interop))
+        .collect();
+
+    let write = |writer: Box<dyn std::io::Write>| match format {
+        "timewarrior" => write_timewarrior(&intervals, writer),
+        _ => write_toggl(&intervals, &config.timesheet.employee, writer),
+    };
+    match output {
+        Some(path) => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            write(Box::new(std::fs::File::create(&path)?))?;
+            eprintln!("Exported {} days to {}", days.len(), path.display());
+        }
+        None => write(Box::new(std::io::stdout().lock()))?,
+    }
+    Ok(())
+}
+
+fn import_intervals(config: Config, format: &str, path: &str, options: &[&str]) -> Result<()> {
+    let replace = match options {
+        [] => false,
+        ["--replace"] => true,
+        _ => bail!("unknown options {:?}", options.join(" ")),
+    };
+    let intervals = match format {
+        "timewarrior" => read_timewarrior(path)?,
+        _ => read_toggl(std::fs::File::open(path).with_context(|| format!("cannot read {}", path))?)?,
+    };
+    let running = intervals.iter().filter(|interval| interval.end.is_none()).count();
+    if running > 0 {
+        eprintln!("Skipped {} running interval(s)", running);
+    }
+    let days = days_from_intervals(&intervals, &config.interop);
+    let (stored, skipped) = store_days(open_default()?.as_ref(), &days, replace)?;
+    println!("Imported {} days from {}", stored, path);
+    if skipped > 0 {
+        println!("Kept {} days that were already stored, use --replace to overwrite them", skipped);
+    }
+    Ok(())
+}
+
+fn parse_export_date(date: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| anyhow!("{:?} is neither an option nor a YYYY-MM-DD date", date))
+}
+
+// FROM and TO of an export; this month up to today by default
+fn export_range(dates: &[NaiveDate], format: &str) -> Result<(NaiveDate, NaiveDate)> {
+    let today = Local::now().date_naive();
+    Ok(match dates {
+        [] => (today.with_day(1).unwrap_or(today), today),
+        [from] => (*from, today),
+        [from, to] => (*from, *to),
+        _ => bail!("export {} takes at most two dates", format),
+    })
+}
+
+fn migrate(jsonl_path: &str) -> Result<()> {
+    let days = migrate_jsonl_to_sqlite(jsonl_path, DEFAULT_SQLITE_PATH)?;
+    println!("Imported {} days from {} into {}", days, jsonl_path, DEFAULT_SQLITE_PATH);
+    Ok(())
+}
@@ -0,0 +1,584 @@
+use crate::calc_time::parse_time;
+use anyhow::{anyhow, Context, Result};
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_CONFIG_PATH: &str = "data/config.toml";
+
+/// User settings, read from `data/config.toml`. Every field has a default, so
+/// the file only needs the values that differ.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub default_starttime: String,
+    pub schedule: Schedule,
+    pub balance: BalanceSettings,
+    pub breaks: BreakRules,
+    pub csv: CsvSettings,
+    pub timesheet: TimesheetSettings,
+    pub calendar: CalendarSettings,
+    pub interop: InteropSettings,
+    pub notes: NotesSettings,
+    pub projects: Vec<Project>,
+    pub clients: Vec<Client>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            default_starttime: String::from("08:45"),
+            schedule: Schedule::default(),
+            balance: BalanceSettings::default(),
+            breaks: BreakRules::default(),
+            csv: CsvSettings::default(),
+            timesheet: TimesheetSettings::default(),
+            calendar: CalendarSettings::default(),
+            interop: InteropSettings::default(),
+            notes: NotesSettings::default(),
+            projects: Vec::new(),
+            clients: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(path)?;
+        let config: Config =
+            toml::from_str(&text).with_context(|| format!("invalid config {}", path.display()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn load_default() -> Result<Self> {
+        Self::load(DEFAULT_CONFIG_PATH)
+    }
+
+    /// Write the defaults to `path` unless a config already exists there.
+    pub fn create_default(path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if path.exists() {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, toml::to_string_pretty(&Self::default())?)?;
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<()> {
+        parse_time(&self.default_starttime)
+            .ok_or_else(|| anyhow!("default_starttime {:?} is not HH:MM", self.default_starttime))?;
+        for weekday in WEEKDAYS {
+            self.schedule.weekday_target(weekday).minutes()?;
+        }
+        for day in &self.schedule.part_time {
+            day.target.minutes()?;
+        }
+        if let Some(preset) = &self.breaks.preset {
+            if preset != "arbzg" {
+                return Err(anyhow!("unknown break preset {:?}, only \"arbzg\" is known", preset));
+            }
+        }
+        for rule in &self.breaks.rules {
+            rule.after.minutes()?;
+            rule.minimum.minutes()?;
+        }
+        for (i, project) in self.projects.iter().enumerate() {
+            if project.name.trim().is_empty() || project.name.contains('/') {
+                return Err(anyhow!("project name {:?} must not be empty or contain '/'", project.name));
+            }
+            if self.projects[..i].iter().any(|p| p.name == project.name) {
+                return Err(anyhow!("project {:?} is listed twice", project.name));
+            }
+            if let Some(client) = project.client.as_deref().filter(|c| self.client(c).is_none()) {
+                return Err(anyhow!("project {:?} belongs to the unknown client {:?}", project.name, client));
+            }
+        }
+        for (i, client) in self.clients.iter().enumerate() {
+            if client.name.trim().is_empty() {
+                return Err(anyhow!("a client has no name"));
+            }
+            if self.clients[..i].iter().any(|c| c.name == client.name) {
+                return Err(anyhow!("client {:?} is listed twice", client.name));
+            }
+            if !client.rate.is_finite() || client.rate < 0.0 {
+                return Err(anyhow!("client {:?} has the invalid rate {}", client.name, client.rate));
+            }
+            if client.increment < 1 {
+                return Err(anyhow!("client {:?} needs an increment of at least 1 minute", client.name));
+            }
+        }
+        if !self.csv.delimiter.is_ascii() {
+            return Err(anyhow!("csv delimiter {:?} is not an ASCII character", self.csv.delimiter));
+        }
+        Ok(())
+    }
+}
+
+/// Target work time, either in hours (`8.5`) or as "H:MM" (`"8:30"`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TargetTime {
+    Hours(f64),
+    Clock(String),
+}
+
+impl TargetTime {
+    pub fn minutes(&self) -> Result<i64> {
+        match self {
+            TargetTime::Hours(hours) if *hours >= 0.0 => Ok((hours * 60.0).round() as i64),
+            TargetTime::Hours(hours) => Err(anyhow!("negative target time {}", hours)),
+            TargetTime::Clock(clock) => {
+                let (hours, minutes) = clock
+                    .split_once(':')
+                    .and_then(|(h, m)| Some((h.trim().parse::<i64>().ok()?, m.trim().parse::<i64>().ok()?)))
+                    .filter(|(h, m)| *h >= 0 && (0..60).contains(m))
+                    .ok_or_else(|| anyhow!("target time {:?} is not H:MM", clock))?;
+                Ok(hours * 60 + minutes)
+            }
+        }
+    }
+
+    fn from_minutes(minutes: i64) -> Self {
+        TargetTime::Clock(format!("{}:{:02}", minutes / 60, minutes % 60))
+    }
+}
+
+/// A date with a target that differs from its weekday's.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartTimeDay {
+    pub date: NaiveDate,
+    pub target: TargetTime,
+}
+
+/// How long a day is supposed to be.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Schedule {
+    pub monday: TargetTime,
+    pub tuesday: TargetTime,
+    pub wednesday: TargetTime,
+    pub thursday: TargetTime,
+    pub friday: TargetTime,
+    pub saturday: TargetTime,
+    pub sunday: TargetTime,
+    /// Holidays, vacation and other dates without a target.
+    pub days_off: Vec<NaiveDate>,
+    pub part_time: Vec<PartTimeDay>,
+}
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+impl Default for Schedule {
+    // 8:20 on weekdays, what `calc_endtime` used to hard-code
+    fn default() -> Self {
+        let workday = TargetTime::from_minutes(8 * 60 + 20);
+        Self {
+            monday: workday.clone(),
+            tuesday: workday.clone(),
+            wednesday: workday.clone(),
+            thursday: workday.clone(),
+            friday: workday,
+            saturday: TargetTime::from_minutes(0),
+            sunday: TargetTime::from_minutes(0),
+            days_off: Vec::new(),
+            part_time: Vec::new(),
+        }
+    }
+}
+
+impl Schedule {
+    fn weekday_target(&self, weekday: Weekday) -> &TargetTime {
+        match weekday {
+            Weekday::Mon => &self.monday,
+            Weekday::Tue => &self.tuesday,
+            Weekday::Wed => &self.wednesday,
+            Weekday::Thu => &self.thursday,
+            Weekday::Fri => &self.friday,
+            Weekday::Sat => &self.saturday,
+            Weekday::Sun => &self.sunday,
+        }
+    }
+
+    /// Minutes the user is supposed to work on `date`.
+    pub fn target_minutes(&self, date: NaiveDate) -> i64 {
+        if self.days_off.contains(&date) {
+            return 0;
+        }
+        let target = self
+            .part_time
+            .iter()
+            .find(|day| day.date == date)
+            .map(|day| &day.target)
+            .unwrap_or_else(|| self.weekday_target(date.weekday()));
+        target.minutes().unwrap_or(0)
+    }
+}
+
+/// Where the overtime account starts and manual bookings on it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BalanceSettings {
+    /// First day that counts. Without it the first stored day is used.
+    pub start_date: Option<NaiveDate>,
+    /// Balance carried over from before `start_date`, in minutes.
+    pub opening_minutes: i64,
+    pub corrections: Vec<Correction>,
+}
+
+/// A manual booking, e.g. paid-out overtime (negative) or a missing day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Correction {
+    pub date: NaiveDate,
+    pub minutes: i64,
+    #[serde(default)]
+    pub note: String,
+}
+
+/// Statutory minimum breaks. Without rules nothing is deducted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BreakRules {
+    /// `"arbzg"`: the German Arbeitszeitgesetz, 30 min after 6 h and 45 min
+    /// after 9 h. Used when `rules` is empty.
+    pub preset: Option<String>,
+    pub rules: Vec<BreakRule>,
+}
+
+/// Working more than `after` requires at least `minimum` of breaks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BreakRule {
+    pub after: TargetTime,
+    pub minimum: TargetTime,
+}
+
+impl BreakRules {
+    /// (after, minimum) pairs in minutes.
+    fn thresholds(&self) -> Vec<(i64, i64)> {
+        if self.rules.is_empty() && self.preset.as_deref() == Some("arbzg") {
+            return vec![(6 * 60, 30), (9 * 60, 45)];
+        }
+        self.rules
+            .iter()
+            .filter_map(|rule| Some((rule.after.minutes().ok()?, rule.minimum.minutes().ok()?)))
+            .collect()
+    }
+
+    /// Minimum break for `worked` minutes of work (breaks not included), and
+    /// the threshold it applies from.
+    pub fn required_break(&self, worked: i64) -> Option<(i64, i64)> {
+        self.thresholds()
+            .into_iter()
+            .filter(|(after, _)| worked > *after)
+            .max_by_key(|(_, minimum)| *minimum)
+    }
+
+    /// Break time to deduct on top of the `taken` minutes. The deduction never
+    /// pushes the work time below the threshold that triggered it.
+    pub fn missing_break(&self, worked: i64, taken: i64) -> i64 {
+        match self.required_break(worked) {
+            Some((after, minimum)) => (minimum - taken).clamp(0, worked - after),
+            None => 0,
+        }
+    }
+
+    /// Minutes of work, breaks not included, after which `target` minutes
+    /// remain once the break still missing besides `taken` is deducted.
+    pub fn work_for_target(&self, target: i64, taken: i64) -> i64 {
+        let mut worked = target;
+        loop {
+            let needed = target + self.missing_break(worked, taken);
+            if needed <= worked {
+                return worked;
+            }
+            worked = needed;
+        }
+    }
+
+    /// Minutes of work left until a rule asks for more break than `taken`;
+    /// zero or less once it is overdue. `None` if no rule is left to break.
+    pub fn next_due(&self, worked: i64, taken: i64) -> Option<i64> {
+        self.thresholds()
+            .into_iter()
+            .filter(|(_, minimum)| *minimum > taken)
+            .map(|(after, _)| after - worked)
+            .min()
+    }
+}
+
+/// Defaults for CSV exports; the command line can override each of them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvSettings {
+    pub delimiter: char,
+    pub layout: CsvLayout,
+    pub unit: CsvUnit,
+}
+
+impl Default for CsvSettings {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            layout: CsvLayout::Days,
+            unit: CsvUnit::Minutes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvLayout {
+    /// One row per day.
+    Days,
+    /// One row per meeting, focus session and break.
+    Sessions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvUnit {
+    Minutes,
+    /// Decimal hours, e.g. `7.50`.
+    Hours,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimesheetSettings {
+    /// Printed in the header of the monthly timesheet.
+    pub employee: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarSettings {
+    /// `.ics` files, or directories whose `.ics` files are all read.
+    pub paths: Vec<String>,
+}
+
+/// Tags that mark meetings, focus sessions and breaks when importing from or
+/// exporting to Timewarrior and Toggl. Matching ignores case; exports use
+/// the first tag of each list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InteropSettings {
+    pub meeting_tags: Vec<String>,
+    pub focus_tags: Vec<String>,
+    pub break_tags: Vec<String>,
+}
+
+impl Default for InteropSettings {
+    fn default() -> Self {
+        Self {
+            meeting_tags: vec![String::from("meeting")],
+            focus_tags: vec![String::from("focus")],
+            break_tags: vec![String::from("break")],
+        }
+    }
+}
+
+/// Where meeting notes are written as Markdown, e.g. into an Obsidian vault.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotesSettings {
+    pub dir: String,
+    pub layout: NotesLayout,
+    /// Write the notes of a day when it is exported.
+    pub export_on_save: bool,
+}
+
+impl Default for NotesSettings {
+    fn default() -> Self {
+        Self {
+            dir: String::from("data/notes"),
+            layout: NotesLayout::Meeting,
+            export_on_save: false,
+        }
+    }
+}
+
+impl NotesSettings {
+    /// The notes directory, with `~/` expanded.
+    pub fn dir_path(&self) -> PathBuf {
+        expand_home(&self.dir)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotesLayout {
+    /// One file per meeting.
+    Meeting,
+    /// One file per day with a section per meeting.
+    Day,
+}
+
+/// A project time can be booked to, from `[[projects]]` in the config.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Project {
+    pub name: String,
+    /// Shown next to the project in reports.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_center: Option<String>,
+    pub tasks: Vec<String>,
+    /// The client billed for the project, from `[[clients]]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+}
+
+/// A client billed for its projects, from `[[clients]]` in the config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Client {
+    pub name: String,
+    /// Per hour, in `currency`.
+    pub rate: f64,
+    pub currency: String,
+    /// Billed minutes are a multiple of this, e.g. 15.
+    pub increment: i64,
+    pub rounding: Rounding,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            rate: 0.0,
+            currency: String::from("EUR"),
+            increment: 1,
+            rounding: Rounding::Up,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rounding {
+    Up,
+    Nearest,
+    Down,
+}
+
+impl Client {
+    /// `minutes` rounded to the client's increment.
+    pub fn billed_minutes(&self, minutes: i64) -> i64 {
+        let increment = self.increment.max(1);
+        let whole = minutes.div_euclid(increment) * increment;
+        let rest = minutes - whole;
+        match self.rounding {
+            _ if rest == 0 => whole,
+            Rounding::Up => whole + increment,
+            Rounding::Nearest if rest * 2 >= increment => whole + increment,
+            Rounding::Nearest | Rounding::Down => whole,
+        }
+    }
+
+    /// The price of `billed` minutes, rounded to cents.
+    pub fn amount(&self, billed: i64) -> f64 {
+        (billed as f64 * self.rate / 60.0 * 100.0).round() / 100.0
+    }
+}
+
+impl Config {
+    pub fn project(&self, name: &str) -> Option<&Project> {
+        self.projects.iter().find(|p| p.name == name)
+    }
+
+    pub fn client(&self, name: &str) -> Option<&Client> {
+        self.clients.iter().find(|c| c.name == name)
+    }
+
+    /// The projects billed to `client`.
+    pub fn client_projects<'a>(&'a self, client: &'a str) -> impl Iterator<Item = &'a Project> + 'a {
+        self.projects.iter().filter(move |p| p.client.as_deref() == Some(client))
+    }
+}
+
+/// `path` with a leading `~/` standing for the home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(increment: i64, rounding: Rounding) -> Client {
+        Client {
+            name: "Acme".to_string(),
+            rate: 90.0,
+            increment,
+            rounding,
+            ..Client::default()
+        }
+    }
+
+    #[test]
+    fn rounding_up_takes_the_next_increment() {
+        let acme = client(15, Rounding::Up);
+        assert_eq!(acme.billed_minutes(1), 15);
+        assert_eq!(acme.billed_minutes(44), 45);
+        assert_eq!(acme.billed_minutes(46), 60);
+    }
+
+    #[test]
+    fn rounding_nearest_goes_up_from_half_the_increment() {
+        let acme = client(15, Rounding::Nearest);
+        assert_eq!(acme.billed_minutes(7), 0);
+        assert_eq!(acme.billed_minutes(8), 15);
+        assert_eq!(acme.billed_minutes(52), 45);
+        assert_eq!(acme.billed_minutes(53), 60);
+        // an even increment rounds its exact half up
+        assert_eq!(client(10, Rounding::Nearest).billed_minutes(25), 30);
+    }
+
+    #[test]
+    fn rounding_down_drops_the_rest() {
+        let acme = client(15, Rounding::Down);
+        assert_eq!(acme.billed_minutes(14), 0);
+        assert_eq!(acme.billed_minutes(59), 45);
+    }
+
+    #[test]
+    fn exact_multiples_stay_as_they_are() {
+        for rounding in [Rounding::Up, Rounding::Nearest, Rounding::Down] {
+            let acme = client(15, rounding);
+            assert_eq!(acme.billed_minutes(0), 0);
+            assert_eq!(acme.billed_minutes(45), 45);
+            assert_eq!(acme.billed_minutes(120), 120);
+        }
+    }
+
+    #[test]
+    fn increments_below_one_bill_the_minutes() {
+        assert_eq!(client(0, Rounding::Up).billed_minutes(37), 37);
+        assert_eq!(client(1, Rounding::Up).billed_minutes(37), 37);
+    }
+
+    #[test]
+    fn amount_is_rounded_to_cents() {
+        assert_eq!(client(15, Rounding::Up).amount(60), 90.0);
+        assert_eq!(client(15, Rounding::Up).amount(20), 30.0);
+        let odd = Client { rate: 100.0, ..client(1, Rounding::Up) };
+        assert_eq!(odd.amount(1), 1.67);
+    }
+}
@@ -0,0 +1,334 @@
+use crate::app::App;
+use crate::calc_time::now;
+use crate::model::{billable_default, is_billable};
+use crate::status::Status;
+use anyhow::Result;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::sync::{mpsc::UnboundedReceiver, oneshot};
+
+pub const DEFAULT_SOCKET_PATH: &str = ".tmp_cache/control.sock";
+
+/// A command for the running tracker, sent as one JSON object per line, e.g.
+/// `{"command":"meeting_start","name":"Standup"}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request {
+    SetStart { time: String },
+    SetEnd { time: String },
+    MeetingStart {
+        name: String,
+        /// Book the meeting to this project instead of the running one.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        project: Option<String>,
+        #[serde(default = "billable_default", skip_serializing_if = "is_billable")]
+        billable: bool,
+    },
+    MeetingStop,
+    MeetingPause,
+    MeetingResume,
+    FocusStart {
+        #[serde(default = "billable_default", skip_serializing_if = "is_billable")]
+        billable: bool,
+    },
+    FocusStop,
+    FocusToggle,
+    FocusPause,
+    FocusResume,
+    BreakStart,
+    BreakStop,
+    ProjectStart {
+        project: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        task: Option<String>,
+    },
+    ProjectStop,
+    /// Mark the running meeting and focus session as billable or not.
+    SetBillable { billable: bool },
+    /// Add today's meetings from the calendars in `paths`, or the configured
+    /// ones if empty.
+    CalendarImport {
+        #[serde(default)]
+        paths: Vec<String>,
+    },
+    Status,
+}
+
+/// The answer to a [`Request`], also one JSON object per line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<Status>,
+}
+
+impl Response {
+    fn ok(message: impl Into<String>) -> Self {
+        Self {
+            ok: true,
+            message: message.into(),
+            status: None,
+        }
+    }
+
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            message: message.into(),
+            status: None,
+        }
+    }
+}
+
+/// A request from a client and where to send the answer.
+pub type ControlMessage = (Request, oneshot::Sender<Response>);
+
+/// Carry out `request` on `app`. The TUI does this for requests from the
+/// socket, the command line for its own when no TUI is running.
+pub fn apply(app: &mut App, request: Request) -> Response {
+    match request {
+        Request::SetStart { time } => {
+            app.starttime_input = time;
+            if !app.save_starttime_value() {
+                let input = std::mem::take(&mut app.starttime_input);
+                return Response::error(format!("{:?} is not a valid time, use HH:MM", input));
+            }
+            Response::ok(format!("Started at {}", app.starttime().format("%H:%M")))
+        }
+        Request::SetEnd { time } => {
+            app.endtime_input = time;
+            if !app.save_endtime_value() {
+                let input = std::mem::take(&mut app.endtime_input);
+                return Response::error(format!("{:?} is not a valid time, use HH:MM", input));
+            }
+            Response::ok(format!("End set to {}", app.endtime_pairs[&app.endtime_key].format("%H:%M")))
+        }
+        Request::MeetingStart { name, project, billable } => {
+            if app.meeting_running {
+                return Response::error(format!("the meeting {:?} is still running", app.meeting_name));
+            }
+            app.meeting_name_input = name;
+            app.start_meeting_with(project.or_else(|| app.running_project()), billable);
+            Response::ok(format!(
+                "Meeting {:?} started at {}",
+                app.meeting_name,
+                app.meeting_start_time.format("%H:%M")
+            ))
+        }
+        Request::MeetingStop => {
+            if !app.meeting_running {
+                return Response::error("no meeting is running");
+            }
+            app.end_meeting();
+            let meeting = app.meeting_list.last().expect("end_meeting adds the meeting");
+            Response::ok(format!(
+                "Meeting {:?} ended after {} min",
+                meeting.meeting_name, meeting.time_in_meeting
+            ))
+        }
+        Request::MeetingPause => {
+            if !app.pause_meeting() {
+                return Response::error(if app.meeting_running {
+                    "the meeting is paused already"
+                } else {
+                    "no meeting is running"
+                });
+            }
+            Response::ok(format!("Meeting {:?} paused after {} min", app.meeting_name, app.meeting_minutes(now())))
+        }
+        Request::MeetingResume => {
+            if !app.resume_meeting() {
+                return Response::error(if app.meeting_running {
+                    "the meeting is not paused"
+                } else {
+                    "no meeting is running"
+                });
+            }
+            Response::ok(format!("Meeting {:?} resumed", app.meeting_name))
+        }
+        Request::FocusStart { .. } if app.focus => Response::error("a focus session is already running"),
+        Request::FocusStop if !app.focus => Response::error("no focus session is running"),
+        Request::FocusStart { billable } => {
+            app.start_focus_time_with(billable);
+            Response::ok(format!("Focus session started at {}", app.focus_time_start.format("%H:%M")))
+        }
+        Request::FocusStop | Request::FocusToggle => {
+            if app.focus {
+                app.end_focus_time();
+                let focus = app.focus_time_list.last().expect("end_focus_time adds the session");
+                Response::ok(format!("Focus session ended after {} min", focus.focus_time))
+            } else {
+                app.start_focus_time();
+                Response::ok(format!("Focus session started at {}", app.focus_time_start.format("%H:%M")))
+            }
+        }
+        Request::FocusPause => {
+            if !app.pause_focus_time() {
+                return Response::error(if app.focus {
+                    "the focus session is paused already"
+                } else {
+                    "no focus session is running"
+                });
+            }
+            Response::ok(format!("Focus session paused after {} min", app.focus_minutes(now())))
+        }
+        Request::FocusResume => {
+            if !app.resume_focus_time() {
+                return Response::error(if app.focus {
+                    "the focus session is not paused"
+                } else {
+                    "no focus session is running"
+                });
+            }
+            Response::ok("Focus session resumed")
+        }
+        Request::BreakStart => {
+            if app.break_running {
+                return Response::error("a break is already running");
+            }
+            app.start_break();
+            Response::ok(format!("Break started at {}", app.break_start_time.format("%H:%M")))
+        }
+        Request::BreakStop => {
+            if !app.break_running {
+                return Response::error("no break is running");
+            }
+            app.end_break();
+            let pause = app.break_list.last().expect("end_break adds the break");
+            Response::ok(format!("Break ended after {} min", pause.time_in_break))
+        }
+        Request::ProjectStart { project, task } => {
+            if project.trim().is_empty() || project.contains('/') {
+                return Response::error(format!("{:?} is not a project name", project));
+            }
+            app.start_project_named(project, task);
+            Response::ok(format!(
+                "Project {} started at {}",
+                app.project_label(),
+                app.project_start_time.format("%H:%M")
+            ))
+        }
+        Request::ProjectStop => {
+            if !app.project_running {
+                return Response::error("no project is running");
+            }
+            app.end_project();
+            let session = app.project_list.last().expect("end_project adds the session");
+            Response::ok(format!("Project {} ended after {} min", session.project, session.time_in_project))
+        }
+        Request::SetBillable { billable } => {
+            if !app.set_billable(billable) {
+                return Response::error("no meeting or focus session is running");
+            }
+            Response::ok(if billable {
+                "Marked the running session as billable"
+            } else {
+                "Marked the running session as non-billable"
+            })
+        }
+        Request::CalendarImport { paths } => {
+            match app.import_calendar_meetings(Local::now().date_naive(), &paths) {
+                Ok(added) => Response::ok(format!("Imported {} meetings from the calendar", added)),
+                Err(e) => Response::error(format!("calendar import failed: {:#}", e)),
+            }
+        }
+        Request::Status => Response {
+            status: Some(Status::of(app, now())),
+            ..Response::ok("")
+        },
+    }
+}
+
+/// Send `request` to a running TUI. `Ok(None)` if no instance is listening.
+#[cfg(unix)]
+pub fn send(path: impl AsRef<Path>, request: &Request) -> Result<Option<Response>> {
+    use std::io::{BufRead, BufReader, ErrorKind, Write};
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = match UnixStream::connect(path) {
+        Ok(stream) => stream,
+        Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    stream.set_read_timeout(Some(std::time::Duration::from_secs(5)))?;
+    writeln!(stream, "{}", serde_json::to_string(request)?)?;
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    Ok(Some(serde_json::from_str(&line)?))
+}
+
+#[cfg(not(unix))]
+pub fn send(_path: impl AsRef<Path>, _request: &Request) -> Result<Option<Response>> {
+    Ok(None)
+}
+
+/// Whether an instance is listening on the socket at `path`.
+#[cfg(unix)]
+pub fn is_running(path: impl AsRef<Path>) -> bool {
+    std::os::unix::net::UnixStream::connect(path).is_ok()
+}
+
+#[cfg(not(unix))]
+pub fn is_running(_path: impl AsRef<Path>) -> bool {
+    false
+}
+
+/// Accept requests on the socket at `path`. Fails if another instance is
+/// listening there already; a socket left behind by a crash is replaced.
+#[cfg(unix)]
+pub fn listen(path: impl AsRef<Path>) -> Result<UnboundedReceiver<ControlMessage>> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixListener;
+
+    let path = path.as_ref();
+    if path.exists() {
+        if is_running(path) {
+            anyhow::bail!("another instance is already running ({})", path.display());
+        }
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<ControlMessage>();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let (reader, mut writer) = stream.into_split();
+                let mut lines = BufReader::new(reader).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let response = match serde_json::from_str::<Request>(&line) {
+                        Ok(request) => {
+                            let (reply_tx, reply_rx) = oneshot::channel();
+                            if tx.send((request, reply_tx)).is_err() {
+                                break;
+                            }
+                            match reply_rx.await {
+                                Ok(response) => response,
+                                Err(_) => break,
+                            }
+                        }
+                        Err(e) => Response::error(format!("invalid request: {}", e)),
+                    };
+                    let Ok(mut json) = serde_json::to_string(&response) else {
+                        break;
+                    };
+                    json.push('\n');
+                    if writer.write_all(json.as_bytes()).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+    Ok(rx)
+}
+
+#[cfg(not(unix))]
+pub fn listen(_path: impl AsRef<Path>) -> Result<UnboundedReceiver<ControlMessage>> {
+    // no socket; the sender is dropped, so nothing ever arrives
+    let (_tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    Ok(rx)
+}
@@ -0,0 +1,115 @@
+use crate::config::{BreakRules, CsvLayout, CsvSettings, CsvUnit};
+use crate::model::{Timestamp, Worktime};
+use crate::store::open_default;
+use anyhow::Result;
+use chrono::NaiveDate;
+use std::io::Write;
+use std::path::PathBuf;
+
+pub const DEFAULT_EXPORT_DIR: &str = "data/export";
+
+/// Where the TUI writes the export of `from` to `to`.
+pub fn default_export_path(from: NaiveDate, to: NaiveDate) -> PathBuf {
+    PathBuf::from(DEFAULT_EXPORT_DIR).join(format!("worktime_{}_{}.csv", from, to))
+}
+
+/// Write the stored days from `from` to `to` (inclusive) as CSV. Returns the
+/// number of days.
+pub fn export_csv(
+    from: NaiveDate,
+    to: NaiveDate,
+    rules: &BreakRules,
+    settings: &CsvSettings,
+    writer: impl Write,
+) -> Result<usize> {
+    let mut days = open_default()?.load_range(&from.to_string(), &to.to_string())?;
+    days.sort_by(|a, b| a.date.cmp(&b.date));
+    write_csv(&days, rules, settings, writer)?;
+    Ok(days.len())
+}
+
+pub fn write_csv(days: &[Worktime], rules: &BreakRules, settings: &CsvSettings, writer: impl Write) -> Result<()> {
+    let mut csv = csv::WriterBuilder::new()
+        .delimiter(settings.delimiter as u8)
+        .from_writer(writer);
+    let duration = |minutes: i64| match settings.unit {
+        CsvUnit::Minutes => minutes.to_string(),
+        CsvUnit::Hours => format!("{:.2}", minutes as f64 / 60.0),
+    };
+
+    match settings.layout {
+        CsvLayout::Days => {
+            csv.write_record(["date", "start", "end", "gross", "net", "breaks", "meetings", "focus"])?;
+            for day in days {
+                let meetings: i64 = day.meetings.iter().map(|m| i64::from(m.time_in_meeting)).sum();
+                let focus: i64 = day.focus_time.iter().map(|f| i64::from(f.focus_time)).sum();
+                csv.write_record([
+                    day.date.clone(),
+                    timestamp(day.starttime),
+                    timestamp(day.endtime),
+                    duration(day.gross_minutes()),
+                    duration(day.worked_minutes(rules)),
+                    duration(day.break_minutes()),
+                    duration(meetings),
+                    duration(focus),
+                ])?;
+            }
+        }
+        CsvLayout::Sessions => {
+            csv.write_record(["date", "kind", "name", "start", "end", "duration", "project"])?;
+            for day in days {
+                for meeting in &day.meetings {
+                    csv.write_record([
+                        day.date.clone(),
+                        "meeting".to_string(),
+                        meeting.meeting_name.clone(),
+                        timestamp(meeting.meeting_start_time),
+                        timestamp(meeting.meeting_end_time),
+                        duration(i64::from(meeting.time_in_meeting)),
+                        meeting.project.clone().unwrap_or_default(),
+                    ])?;
+                }
+                for focus in &day.focus_time {
+                    csv.write_record([
+                        day.date.clone(),
+                        "focus".to_string(),
+                        String::new(),
+                        timestamp(focus.focus_time_start),
+                        timestamp(focus.focus_time_end),
+                        duration(i64::from(focus.focus_time)),
+                        focus.project.clone().unwrap_or_default(),
+                    ])?;
+                }
+                for pause in &day.breaks {
+                    csv.write_record([
+                        day.date.clone(),
+                        "break".to_string(),
+                        String::new(),
+                        timestamp(pause.break_start_time),
+                        timestamp(pause.break_end_time),
+                        duration(i64::from(pause.time_in_break)),
+                        String::new(),
+                    ])?;
+                }
+                for session in &day.projects {
+                    csv.write_record([
+                        day.date.clone(),
+                        "project".to_string(),
+                        session.task.clone().unwrap_or_default(),
+                        timestamp(session.project_start_time),
+                        timestamp(session.project_end_time),
+                        duration(i64::from(session.time_in_project)),
+                        session.project.clone(),
+                    ])?;
+                }
+            }
+        }
+    }
+    csv.flush()?;
+    Ok(())
+}
+
+// Date included, so a shift past midnight stays readable in a spreadsheet.
+fn timestamp(time: Timestamp) -> String {
+    time.format("%Y-%m-%d %H:%M").to_string()
+}
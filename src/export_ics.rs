@@ -0,0 +1,122 @@
+use crate::model::{unpaused_spans, Timestamp, Worktime};
+use crate::store::open_default;
+use anyhow::Result;
+use chrono::{NaiveDate, Utc};
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Write the stored days from `from` to `to` (inclusive) as an iCalendar file.
+/// Returns the number of days.
+pub fn export_ics(from: NaiveDate, to: NaiveDate, writer: impl Write) -> Result<usize> {
+    let mut days = open_default()?.load_range(&from.to_string(), &to.to_string())?;
+    days.sort_by(|a, b| a.date.cmp(&b.date));
+    write_ics(&days, writer)?;
+    Ok(days.len())
+}
+
+/// One VEVENT per work block, meeting and focus session. A day's work blocks
+/// are its time from start to end with the breaks cut out; a paused meeting
+/// or focus session is cut at its pauses the same way. The UIDs only
+/// depend on the data, so importing a newer export updates the events instead
+/// of duplicating them; events of a kind that start at the same second are
+/// told apart by their position.
+pub fn write_ics(days: &[Worktime], mut writer: impl Write) -> Result<()> {
+    let mut calendar = IcsWriter {
+        out: String::new(),
+        stamp: Utc::now().format("%Y%m%dT%H%M%SZ").to_string(),
+        uids: HashMap::new(),
+    };
+    calendar.out.push_str("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//time_tracking_basic//EN\r\nCALSCALE:GREGORIAN\r\n");
+    for day in days {
+        for (start, end) in work_blocks(day) {
+            calendar.event("work", "Work", start, end);
+        }
+        for meeting in &day.meetings {
+            let summary = format!("Meeting: {}", meeting.meeting_name);
+            for (start, end) in unpaused_spans(meeting.meeting_start_time, meeting.meeting_end_time, &meeting.pauses) {
+                calendar.event("meeting", &summary, start, end);
+            }
+        }
+        for focus in &day.focus_time {
+            for (start, end) in unpaused_spans(focus.focus_time_start, focus.focus_time_end, &focus.pauses) {
+                calendar.event("focus", "Focus time", start, end);
+            }
+        }
+    }
+    let mut out = calendar.out;
+    out.push_str("END:VCALENDAR\r\n");
+    writer.write_all(out.as_bytes())?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn work_blocks(day: &Worktime) -> Vec<(Timestamp, Timestamp)> {
+    let mut breaks: Vec<_> = day.breaks.iter().map(|b| (b.break_start_time, b.break_end_time)).collect();
+    breaks.sort();
+    let mut blocks = Vec::new();
+    let mut from = day.starttime;
+    for (start, end) in breaks {
+        if start > from {
+            blocks.push((from, start.min(day.endtime)));
+        }
+        from = from.max(end);
+    }
+    if day.endtime > from {
+        blocks.push((from, day.endtime));
+    }
+    blocks
+}
+
+struct IcsWriter {
+    out: String,
+    stamp: String,
+    /// How often each UID was handed out so far.
+    uids: HashMap<String, usize>,
+}
+
+impl IcsWriter {
+    fn event(&mut self, kind: &str, summary: &str, start: Timestamp, end: Timestamp) {
+        let utc = |time: Timestamp| time.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string();
+        let mut uid = format!("{}-{}", utc(start), kind);
+        let seen = self.uids.entry(uid.clone()).or_default();
+        *seen += 1;
+        if *seen > 1 {
+            uid = format!("{}-{}", uid, seen);
+        }
+        let lines = [
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}@time_tracking_basic", uid),
+            format!("DTSTAMP:{}", self.stamp),
+            format!("DTSTART:{}", utc(start)),
+            format!("DTEND:{}", utc(end)),
+            format!("SUMMARY:{}", escape(summary)),
+            format!("CATEGORIES:{}", kind),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ];
+        for line in lines {
+            fold(&mut self.out, &line);
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// Lines longer than 75 octets continue on the next line after a space.
+fn fold(out: &mut String, line: &str) {
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            length = 1;
+        }
+        out.push(c);
+        length += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
@@ -1,76 +1,20 @@
-use serde::{Deserialize, Serialize};
-use std::fs::OpenOptions;
-use std::io::{Result, Write};
-
-#[derive(Clone, Serialize, Deserialize)]
-pub struct MeetingList {
-    pub meeting_name: String,
-    pub meeting_start_time: String,
-    pub meeting_end_time: String,
-    pub time_in_meeting: i32,
-}
-
-
-#[derive(Clone, Serialize, Deserialize)]
-pub struct FocusTime {
-    pub focus_time_start: String,
-    pub focus_time_end: String,
-    pub focus_time: i32,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct Worktime {
-    pub date: String,
-    pub starttime: String,
-    pub endtime: String,
-    pub meetings: Vec<MeetingList>,
-    pub focus_time: Vec<FocusTime>,
-}
+use crate::model::{merge_duplicate_days, Worktime};
+use crate::store::{open_default, WorktimeStore};
+use anyhow::Result;
 
 impl Worktime {
-    pub fn new(
-        date: String,
-        starttime: String,
-        endtime: String,
-        meetings: Vec<MeetingList>,
-        focus_time: Vec<FocusTime>,
-    ) -> Self {
-        Worktime {
-            date,
-            starttime,
-            endtime,
-            meetings,
-            focus_time,
-        }
-    }
-
-    fn to_jsonl(&self) -> String {
-        serde_json::to_string(self).unwrap()
+    pub fn export_json(&mut self) -> Result<()> {
+        self.export_to(open_default()?.as_ref())
     }
 
-    pub fn export_json(&mut self) -> Result<()> {
-        let filename = "data/worktime.jsonl";
-        if std::path::Path::new(filename).exists() {
-            append_worktime_to_jsonl(self, filename)?;
-        } else {
-            create_and_write_jsonl(self, filename)?;
+    /// Merge this day into the record already stored for its date, if any.
+    pub fn export_to(&self, store: &dyn WorktimeStore) -> Result<()> {
+        let mut record = self.clone();
+        let stored = store.load_range(&self.date, &self.date)?;
+        if let Some(mut existing) = merge_duplicate_days(stored).pop() {
+            existing.merge(record);
+            record = existing;
         }
-        Ok(())
+        store.upsert(&record)
     }
 }
-
-fn append_worktime_to_jsonl(worktime: &Worktime, filename: &str) -> Result<()> {
-    let file = OpenOptions::new().append(true).open(filename)?;
-    writeln!(&file, "{}", worktime.to_jsonl())?;
-    Ok(())
-}
-
-fn create_and_write_jsonl(worktime: &Worktime, filename: &str) -> Result<()> {
-    let file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(filename)?;
-    writeln!(&file, "{}", worktime.to_jsonl())?;
-    Ok(())
-}
\ No newline at end of file
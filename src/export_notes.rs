@@ -0,0 +1,130 @@
+use crate::config::NotesLayout;
+use crate::model::{MeetingList, Worktime};
+use anyhow::Result;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Write the notes of the meetings of `days` as Markdown files into `dir`,
+/// one per meeting or one per day, with the action items as a task list.
+/// Meetings with neither notes nor action items are left out. The
+/// front matter carries the meeting's name, date, times and duration in
+/// minutes, so vaults like Obsidian can query them. Existing files of the
+/// same meetings are overwritten. Returns the written paths.
+pub fn write_notes(days: &[Worktime], dir: impl AsRef<Path>, layout: NotesLayout) -> Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    let mut paths = Vec::new();
+    for day in days {
+        let meetings: Vec<&MeetingList> = day
+            .meetings
+            .iter()
+            .filter(|m| !m.notes.trim().is_empty() || !m.action_items.is_empty())
+            .collect();
+        if meetings.is_empty() {
+            continue;
+        }
+        std::fs::create_dir_all(dir)?;
+        match layout {
+            NotesLayout::Meeting => {
+                for meeting in meetings {
+                    let stem = format!(
+                        "{} {} {}",
+                        day.date,
+                        meeting.meeting_start_time.format("%H-%M"),
+                        file_name(&meeting.meeting_name)
+                    );
+                    let mut path = dir.join(format!("{}.md", stem));
+                    let mut n = 2;
+                    while paths.contains(&path) {
+                        path = dir.join(format!("{} {}.md", stem, n));
+                        n += 1;
+                    }
+                    std::fs::write(&path, meeting_markdown(&day.date, meeting))?;
+                    paths.push(path);
+                }
+            }
+            NotesLayout::Day => {
+                let path = dir.join(format!("{}.md", day.date));
+                std::fs::write(&path, day_markdown(&day.date, &meetings))?;
+                paths.push(path);
+            }
+        }
+    }
+    Ok(paths)
+}
+
+fn meeting_markdown(date: &str, meeting: &MeetingList) -> String {
+    let mut out = String::from("---\n");
+    let _ = writeln!(out, "title: {}", quote(&meeting.meeting_name));
+    let _ = writeln!(out, "date: {}", date);
+    front_matter_times(&mut out, "", meeting);
+    if let Some(project) = &meeting.project {
+        let _ = writeln!(out, "project: {}", quote(project));
+    }
+    let _ = writeln!(out, "tags: [meeting]\n---\n");
+    let _ = writeln!(out, "# {}", meeting.meeting_name);
+    notes(&mut out, "##", meeting);
+    out
+}
+
+fn day_markdown(date: &str, meetings: &[&MeetingList]) -> String {
+    let mut out = String::from("---\n");
+    let _ = writeln!(out, "date: {}", date);
+    let _ = writeln!(out, "meetings:");
+    for meeting in meetings {
+        let _ = writeln!(out, "  - name: {}", quote(&meeting.meeting_name));
+        front_matter_times(&mut out, "    ", meeting);
+        if let Some(project) = &meeting.project {
+            let _ = writeln!(out, "    project: {}", quote(project));
+        }
+    }
+    let _ = writeln!(out, "tags: [meeting]\n---\n");
+    let _ = writeln!(out, "# Meetings {}", date);
+    for meeting in meetings {
+        let _ = writeln!(
+            out,
+            "\n## {} {}",
+            meeting.meeting_start_time.format("%H:%M"),
+            meeting.meeting_name
+        );
+        notes(&mut out, "###", meeting);
+    }
+    out
+}
+
+// The notes, then the action items as a task list under a `heading`
+fn notes(out: &mut String, heading: &str, meeting: &MeetingList) {
+    if !meeting.notes.trim().is_empty() {
+        let _ = writeln!(out, "\n{}", meeting.notes.trim_end());
+    }
+    if meeting.action_items.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "\n{} Action items\n", heading);
+    for item in &meeting.action_items {
+        let _ = writeln!(out, "- [{}] {}", if item.done { "x" } else { " " }, item.describe());
+    }
+}
+
+fn front_matter_times(out: &mut String, indent: &str, meeting: &MeetingList) {
+    // Quoted, as YAML 1.1 reads an unquoted 09:30 as a number
+    let _ = writeln!(out, "{}start: \"{}\"", indent, meeting.meeting_start_time.format("%H:%M"));
+    let _ = writeln!(out, "{}end: \"{}\"", indent, meeting.meeting_end_time.format("%H:%M"));
+    let _ = writeln!(out, "{}duration: {}", indent, meeting.time_in_meeting);
+}
+
+// A double-quoted YAML string
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// The meeting name without characters that paths or wiki links do not allow
+fn file_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if "/\\:*?\"<>|#^[]".contains(c) || c.is_control() { '-' } else { c })
+        .collect();
+    match cleaned.trim() {
+        "" => String::from("Meeting"),
+        trimmed => trimmed.to_string(),
+    }
+}
@@ -0,0 +1,301 @@
+mod timewarrior;
+mod toggl;
+
+pub use timewarrior::{read_timewarrior, write_timewarrior};
+pub use toggl::{read_toggl, write_toggl};
+
+use crate::calc_time::minutes_between;
+use crate::config::InteropSettings;
+use crate::model::{unpaused_spans, BreakTime, FocusTime, MeetingList, ProjectTime, Timestamp, Worktime};
+use crate::store::WorktimeStore;
+use anyhow::Result;
+use std::collections::{BTreeMap, HashSet};
+
+/// A tracked span of time as other trackers keep it: tagged, described, and
+/// without any notion of days.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interval {
+    pub start: Timestamp,
+    /// `None` while the interval is still running.
+    pub end: Option<Timestamp>,
+    pub tags: Vec<String>,
+    pub description: String,
+    /// Toggl keeps these in columns; Timewarrior only has tags.
+    pub project: Option<String>,
+    pub task: Option<String>,
+    /// Toggl's Billable column; Timewarrior intervals are all billable.
+    pub billable: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Work,
+    Meeting,
+    Focus,
+    Break,
+}
+
+impl InteropSettings {
+    /// Breaks win over meetings, meetings over focus time.
+    pub fn kind_of(&self, tags: &[String]) -> Kind {
+        let tagged = |known: &[String]| tags.iter().any(|tag| known.iter().any(|k| k.eq_ignore_ascii_case(tag)));
+        if tagged(&self.break_tags) {
+            Kind::Break
+        } else if tagged(&self.meeting_tags) {
+            Kind::Meeting
+        } else if tagged(&self.focus_tags) {
+            Kind::Focus
+        } else {
+            Kind::Work
+        }
+    }
+
+    /// The tag written for `kind`: the first configured one.
+    pub fn tag_of(&self, kind: Kind) -> Option<&str> {
+        let tags = match kind {
+            Kind::Work => return None,
+            Kind::Meeting => &self.meeting_tags,
+            Kind::Focus => &self.focus_tags,
+            Kind::Break => &self.break_tags,
+        };
+        tags.first().map(String::as_str)
+    }
+
+    fn is_kind_tag(&self, tag: &str) -> bool {
+        [&self.meeting_tags, &self.focus_tags, &self.break_tags]
+            .iter()
+            .any(|known| known.iter().any(|k| k.eq_ignore_ascii_case(tag)))
+    }
+
+    fn other_tags<'a>(&self, interval: &'a Interval) -> Vec<&'a str> {
+        interval
+            .tags
+            .iter()
+            .map(String::as_str)
+            .filter(|tag| !self.is_kind_tag(tag))
+            .collect()
+    }
+
+    /// The description of an interval, or its other tags if it has none.
+    fn name_of(&self, interval: &Interval) -> String {
+        if !interval.description.is_empty() {
+            return interval.description.clone();
+        }
+        self.other_tags(interval).join(" ")
+    }
+
+    /// The project column, or else the first tag that is not a kind tag. A
+    /// meeting without a description is named by its tags instead.
+    fn project_of(&self, interval: &Interval, kind: Kind) -> Option<String> {
+        if interval.project.is_some() {
+            return interval.project.clone();
+        }
+        if kind == Kind::Meeting && interval.description.is_empty() {
+            return None;
+        }
+        self.other_tags(interval).first().map(|tag| tag.to_string())
+    }
+}
+
+/// Group finished intervals into days by their local start date. A day runs
+/// from its first start to its last end; the gaps in between become breaks,
+/// as do intervals with a break tag. Work intervals with a project become
+/// project sessions. Running intervals are left out.
+pub fn days_from_intervals(intervals: &[Interval], settings: &InteropSettings) -> Vec<Worktime> {
+    let mut by_date: BTreeMap<String, Vec<(Timestamp, Timestamp, &Interval)>> = BTreeMap::new();
+    for interval in intervals {
+        if let Some(end) = interval.end.filter(|end| *end > interval.start) {
+            let date = interval.start.date_naive().to_string();
+            by_date.entry(date).or_default().push((interval.start, end, interval));
+        }
+    }
+
+    by_date
+        .into_iter()
+        .map(|(date, mut spans)| {
+            spans.sort_by_key(|(start, _, _)| *start);
+            let mut meetings = Vec::new();
+            let mut focus_time = Vec::new();
+            let mut breaks = Vec::new();
+            let mut projects = Vec::new();
+            let mut reached = spans[0].0;
+            for (start, end, interval) in &spans {
+                let (start, end) = (*start, *end);
+                if start > reached {
+                    breaks.push(break_time(reached, start));
+                }
+                reached = reached.max(end);
+                let kind = settings.kind_of(&interval.tags);
+                let project = settings.project_of(interval, kind);
+                match kind {
+                    Kind::Work => projects.extend(project.map(|project| ProjectTime {
+                        project,
+                        task: interval.task.clone(),
+                        project_start_time: start,
+                        project_end_time: end,
+                        time_in_project: minutes_between(start, end) as i32,
+                    })),
+                    Kind::Meeting => {
+                        let name = settings.name_of(interval);
+                        meetings.push(MeetingList {
+                            meeting_name: if name.is_empty() { "Meeting".to_string() } else { name },
+                            meeting_start_time: start,
+                            meeting_end_time: end,
+                            time_in_meeting: minutes_between(start, end) as i32,
+                            project,
+                            billable: interval.billable,
+                            notes: String::new(),
+                            action_items: Vec::new(),
+                            pauses: Vec::new(),
+                        });
+                    }
+                    Kind::Focus => focus_time.push(FocusTime {
+                        focus_time_start: start,
+                        focus_time_end: end,
+                        focus_time: minutes_between(start, end) as i32,
+                        project,
+                        billable: interval.billable,
+                        pauses: Vec::new(),
+                    }),
+                    Kind::Break => breaks.push(break_time(start, end)),
+                }
+            }
+            Worktime::new(date, spans[0].0, reached, meetings, focus_time, breaks, projects)
+        })
+        .collect()
+}
+
+fn break_time(start: Timestamp, end: Timestamp) -> BreakTime {
+    BreakTime {
+        break_start_time: start,
+        break_end_time: end,
+        time_in_break: minutes_between(start, end) as i32,
+    }
+}
+
+/// Cut a day into intervals that do not overlap, as Timewarrior requires:
+/// meetings and focus sessions are taken out of the work time, except while
+/// paused, breaks are left out entirely. Work time inside a project session carries the project.
+pub fn intervals_from_day(day: &Worktime, settings: &InteropSettings) -> Vec<Interval> {
+    struct Session {
+        start: Timestamp,
+        end: Timestamp,
+        kind: Kind,
+        name: String,
+        project: Option<String>,
+        task: Option<String>,
+        billable: bool,
+    }
+    // in order of precedence
+    let mut sessions: Vec<Session> = Vec::new();
+    for pause in &day.breaks {
+        sessions.push(Session {
+            start: pause.break_start_time,
+            end: pause.break_end_time,
+            kind: Kind::Break,
+            name: String::new(),
+            project: None,
+            task: None,
+            billable: true,
+        });
+    }
+    for meeting in &day.meetings {
+        for (start, end) in unpaused_spans(meeting.meeting_start_time, meeting.meeting_end_time, &meeting.pauses) {
+            sessions.push(Session {
+                start,
+                end,
+                kind: Kind::Meeting,
+                name: meeting.meeting_name.clone(),
+                project: meeting.project.clone(),
+                task: None,
+                billable: meeting.billable,
+            });
+        }
+    }
+    for focus in &day.focus_time {
+        for (start, end) in unpaused_spans(focus.focus_time_start, focus.focus_time_end, &focus.pauses) {
+            sessions.push(Session {
+                start,
+                end,
+                kind: Kind::Focus,
+                name: String::new(),
+                project: focus.project.clone(),
+                task: None,
+                billable: focus.billable,
+            });
+        }
+    }
+    for session in &day.projects {
+        sessions.push(Session {
+            start: session.project_start_time,
+            end: session.project_end_time,
+            kind: Kind::Work,
+            name: String::new(),
+            project: Some(session.project.clone()),
+            task: session.task.clone(),
+            billable: true,
+        });
+    }
+
+    let mut bounds: Vec<Timestamp> = vec![day.starttime, day.endtime];
+    for session in &sessions {
+        bounds.extend([session.start, session.end].into_iter().filter(|t| (day.starttime..=day.endtime).contains(t)));
+    }
+    bounds.sort();
+    bounds.dedup();
+
+    let mut intervals: Vec<Interval> = Vec::new();
+    for pair in bounds.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        let session = sessions.iter().find(|s| s.start <= from && to <= s.end);
+        let kind = session.map_or(Kind::Work, |s| s.kind);
+        if kind == Kind::Break {
+            continue;
+        }
+        let name = session.map(|s| s.name.clone()).unwrap_or_default();
+        let project = session.and_then(|s| s.project.clone());
+        let task = session.and_then(|s| s.task.clone());
+        let billable = session.is_none_or(|s| s.billable);
+        let tags: Vec<String> = settings
+            .tag_of(kind)
+            .map(str::to_string)
+            .into_iter()
+            .chain(project.clone())
+            .collect();
+        match intervals.last_mut() {
+            Some(last)
+                if last.end == Some(from)
+                    && last.tags == tags
+                    && last.description == name
+                    && last.task == task
+                    && last.billable == billable =>
+            {
+                last.end = Some(to)
+            }
+            _ => intervals.push(Interval {
+                start: from,
+                end: Some(to),
+                tags,
+                description: name,
+                project,
+                task,
+                billable,
+            }),
+        }
+    }
+    intervals
+}
+
+/// Store imported days. Dates already in `store` are kept unless `replace`
+/// is set. Returns (stored, skipped).
+pub fn store_days(store: &dyn WorktimeStore, days: &[Worktime], replace: bool) -> Result<(usize, usize)> {
+    let existing: HashSet<String> = store.load_all()?.into_iter().map(|day| day.date).collect();
+    let mut stored = 0;
+    for day in days {
+        if replace || !existing.contains(&day.date) {
+            store.upsert(day)?;
+            stored += 1;
+        }
+    }
+    Ok((stored, days.len() - stored))
+}
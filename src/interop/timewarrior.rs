@@ -0,0 +1,140 @@
+use super::Interval;
+use crate::model::Timestamp;
+use anyhow::{anyhow, Context, Result};
+use chrono::{Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// One entry of `timew export`, which `timew import` reads back.
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportedInterval {
+    start: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    annotation: String,
+}
+
+/// Read Timewarrior intervals from the JSON of `timew export`, from a data
+/// file (`2024-06.data`) or from a data directory such as
+/// `~/.timewarrior/data`.
+pub fn read_timewarrior(path: impl AsRef<Path>) -> Result<Vec<Interval>> {
+    let path = path.as_ref();
+    let files = if path.is_dir() {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(path)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "data"))
+            .collect();
+        files.sort();
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    let mut intervals = Vec::new();
+    for file in files {
+        let text = std::fs::read_to_string(&file).with_context(|| format!("cannot read {}", file.display()))?;
+        if text.trim_start().starts_with('[') {
+            let exported: Vec<ExportedInterval> =
+                serde_json::from_str(&text).with_context(|| format!("invalid timew export {}", file.display()))?;
+            for entry in exported {
+                intervals.push(Interval {
+                    start: parse_timestamp(&entry.start)?,
+                    end: entry.end.as_deref().map(parse_timestamp).transpose()?,
+                    tags: entry.tags,
+                    description: entry.annotation,
+                    project: None,
+                    task: None,
+                    billable: true,
+                });
+            }
+        } else {
+            for (number, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+                let interval = parse_data_line(line)
+                    .with_context(|| format!("{}:{}: invalid interval", file.display(), number + 1))?;
+                intervals.push(interval);
+            }
+        }
+    }
+    Ok(intervals)
+}
+
+/// Write `intervals` as a JSON array for `timew import`. Projects are among
+/// the tags already; tasks have no place in Timewarrior and are dropped.
+pub fn write_timewarrior(intervals: &[Interval], mut writer: impl Write) -> Result<()> {
+    let exported: Vec<ExportedInterval> = intervals
+        .iter()
+        .map(|interval| ExportedInterval {
+            start: format_timestamp(interval.start),
+            end: interval.end.map(format_timestamp),
+            tags: interval.tags.clone(),
+            annotation: interval.description.clone(),
+        })
+        .collect();
+    serde_json::to_writer_pretty(&mut writer, &exported)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+// inc 20240603T070000Z - 20240603T153000Z # tag "two words" # "annotation"
+fn parse_data_line(line: &str) -> Result<Interval> {
+    let rest = line.trim().strip_prefix("inc ").ok_or_else(|| anyhow!("expected \"inc\""))?;
+    let (times, tail) = rest.split_once(" # ").unwrap_or((rest, ""));
+    let (start, end) = match times.split_once(" - ") {
+        Some((start, end)) => (start, Some(end)),
+        None => (times, None),
+    };
+    let tail = tail.trim();
+    let (tags, annotation) = match tail.split_once(" # ") {
+        Some((tags, annotation)) => (tags, annotation),
+        None if tail.starts_with("# ") => ("", &tail[2..]),
+        None => (tail, ""),
+    };
+    Ok(Interval {
+        start: parse_timestamp(start.trim())?,
+        end: end.map(|end| parse_timestamp(end.trim())).transpose()?,
+        tags: split_tags(tags),
+        description: unquote(annotation.trim()),
+        project: None,
+        task: None,
+        billable: true,
+    })
+}
+
+// Tags are separated by spaces; tags with spaces are in double quotes.
+fn split_tags(text: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' if quoted => current.extend(chars.next()),
+            ' ' if !quoted => tags.extend((!current.is_empty()).then(|| std::mem::take(&mut current))),
+            c => current.push(c),
+        }
+    }
+    tags.extend((!current.is_empty()).then_some(current));
+    tags
+}
+
+fn unquote(text: &str) -> String {
+    text.strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .unwrap_or(text)
+        .replace("\\\"", "\"")
+}
+
+fn parse_timestamp(text: &str) -> Result<Timestamp> {
+    let utc = NaiveDateTime::parse_from_str(text, "%Y%m%dT%H%M%SZ")
+        .map_err(|_| anyhow!("{:?} is not a Timewarrior timestamp", text))?;
+    Ok(utc.and_utc().with_timezone(&Local).fixed_offset())
+}
+
+fn format_timestamp(time: Timestamp) -> String {
+    time.naive_utc().format("%Y%m%dT%H%M%SZ").to_string()
+}
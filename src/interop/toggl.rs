@@ -0,0 +1,123 @@
+use super::Interval;
+use crate::calc_time::local_timestamp;
+use crate::model::Timestamp;
+use anyhow::{anyhow, Context, Result};
+use chrono::{Duration, NaiveDate, NaiveTime};
+use std::io::{Read, Write};
+
+/// The columns of Toggl Track's detailed CSV report, which its CSV import
+/// also understands.
+const HEADER: [&str; 13] = [
+    "User",
+    "Email",
+    "Client",
+    "Project",
+    "Task",
+    "Description",
+    "Billable",
+    "Start date",
+    "Start time",
+    "End date",
+    "End time",
+    "Duration",
+    "Tags",
+];
+
+/// Read the time entries of a Toggl CSV export. Columns are found by name;
+/// an entry needs a start date and time and either an end or a duration.
+pub fn read_toggl(reader: impl Read) -> Result<Vec<Interval>> {
+    let mut csv = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers = csv.headers()?.clone();
+    let column = |name: &str| headers.iter().position(|h| h.trim().eq_ignore_ascii_case(name));
+    let start_date = column("Start date").ok_or_else(|| anyhow!("no \"Start date\" column"))?;
+    let start_time = column("Start time").ok_or_else(|| anyhow!("no \"Start time\" column"))?;
+    let (end_date, end_time, duration) = (column("End date"), column("End time"), column("Duration"));
+    let (description, tags) = (column("Description"), column("Tags"));
+    let (project, task, billable) = (column("Project"), column("Task"), column("Billable"));
+
+    let mut intervals = Vec::new();
+    for (number, record) in csv.records().enumerate() {
+        let record = record?;
+        let field = |index: Option<usize>| index.and_then(|i| record.get(i)).map(str::trim).unwrap_or_default();
+        let line = number + 2;
+        let start = timestamp(field(Some(start_date)), field(Some(start_time)))
+            .with_context(|| format!("line {}: invalid start", line))?;
+        let end = match (field(end_date), field(end_time)) {
+            (date, time) if !date.is_empty() && !time.is_empty() => {
+                timestamp(date, time).with_context(|| format!("line {}: invalid end", line))?
+            }
+            _ => start + parse_duration(field(duration)).with_context(|| format!("line {}: no end or duration", line))?,
+        };
+        intervals.push(Interval {
+            start,
+            end: Some(end),
+            tags: field(tags)
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_string)
+                .collect(),
+            description: field(description).to_string(),
+            project: Some(field(project).to_string()).filter(|p| !p.is_empty()),
+            task: Some(field(task).to_string()).filter(|t| !t.is_empty()),
+            billable: !field(billable).eq_ignore_ascii_case("no"),
+        });
+    }
+    Ok(intervals)
+}
+
+/// Write `intervals` in the layout of Toggl's detailed CSV report. `user`
+/// fills the User column.
+pub fn write_toggl(intervals: &[Interval], user: &str, writer: impl Write) -> Result<()> {
+    let mut csv = csv::Writer::from_writer(writer);
+    csv.write_record(HEADER)?;
+    for interval in intervals {
+        let Some(end) = interval.end else {
+            continue;
+        };
+        let seconds = (end - interval.start).num_seconds().max(0);
+        csv.write_record([
+            user.to_string(),
+            String::new(),
+            String::new(),
+            interval.project.clone().unwrap_or_default(),
+            interval.task.clone().unwrap_or_default(),
+            interval.description.clone(),
+            if interval.billable { "Yes" } else { "No" }.to_string(),
+            interval.start.format("%Y-%m-%d").to_string(),
+            interval.start.format("%H:%M:%S").to_string(),
+            end.format("%Y-%m-%d").to_string(),
+            end.format("%H:%M:%S").to_string(),
+            format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60),
+            interval
+                .tags
+                .iter()
+                .filter(|tag| Some(*tag) != interval.project.as_ref())
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", "),
+        ])?;
+    }
+    csv.flush()?;
+    Ok(())
+}
+
+fn timestamp(date: &str, time: &str) -> Result<Timestamp> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")?;
+    let time = NaiveTime::parse_from_str(time, "%H:%M:%S").or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))?;
+    Ok(local_timestamp(date, time))
+}
+
+// "H:MM:SS", hours may exceed 24
+fn parse_duration(text: &str) -> Result<Duration> {
+    let parts: Vec<i64> = text
+        .split(':')
+        .map(|part| part.parse::<i64>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| anyhow!("{:?} is not a duration", text))?;
+    match parts.as_slice() {
+        [hours, minutes, seconds] => Ok(Duration::seconds(hours * 3600 + minutes * 60 + seconds)),
+        [hours, minutes] => Ok(Duration::minutes(hours * 60 + minutes)),
+        _ => Err(anyhow!("{:?} is not a duration", text)),
+    }
+}
@@ -0,0 +1,305 @@
+use crate::calc_time::format_minutes;
+use crate::config::Config;
+use crate::model::Worktime;
+use crate::report::{escape_html, escape_markdown_cell, Period};
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// The billable time of one project and task on one day.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InvoiceLine {
+    pub date: NaiveDate,
+    pub project: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    /// Tracked minutes.
+    pub minutes: i64,
+    /// Minutes after rounding to the client's increment.
+    pub billed_minutes: i64,
+    pub amount: f64,
+}
+
+/// A draft invoice for one client and period. Each line is rounded on its
+/// own, the way a timesheet would be checked line by line.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Invoice {
+    pub client: String,
+    #[serde(skip)]
+    pub period: Period,
+    /// The period's title, e.g. "June 2024".
+    #[serde(rename = "period")]
+    pub title: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub currency: String,
+    pub rate: f64,
+    pub lines: Vec<InvoiceLine>,
+}
+
+impl Invoice {
+    /// Bill the stored days of `period` to `client`: the billable time of the
+    /// client's projects, per day, project and task.
+    pub fn build(worktime: &[Worktime], config: &Config, client: &str, period: Period) -> Result<Self> {
+        let settings = config
+            .client(client)
+            .ok_or_else(|| anyhow!("unknown client {:?}, add it to [[clients]] in the config", client))?;
+        let projects: Vec<&str> = config.client_projects(client).map(|p| p.name.as_str()).collect();
+        if projects.is_empty() {
+            return Err(anyhow!("no project belongs to client {:?}", client));
+        }
+
+        let mut minutes: BTreeMap<(NaiveDate, String, Option<String>), i64> = BTreeMap::new();
+        for day in worktime {
+            let Ok(date) = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d") else {
+                continue;
+            };
+            if date < period.first_day() || date > period.last_day() {
+                continue;
+            }
+            for ((project, task), booked) in day.task_minutes(true) {
+                if projects.contains(&project.as_str()) {
+                    *minutes.entry((date, project, task)).or_default() += booked;
+                }
+            }
+        }
+        let lines = minutes
+            .into_iter()
+            .filter(|(_, minutes)| *minutes > 0)
+            .map(|((date, project, task), minutes)| {
+                let billed_minutes = settings.billed_minutes(minutes);
+                InvoiceLine {
+                    date,
+                    project,
+                    task,
+                    minutes,
+                    billed_minutes,
+                    amount: settings.amount(billed_minutes),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            client: settings.name.clone(),
+            period,
+            title: period.title(),
+            from: period.first_day(),
+            to: period.last_day(),
+            currency: settings.currency.clone(),
+            rate: settings.rate,
+            lines,
+        })
+    }
+
+    pub fn total_minutes(&self) -> i64 {
+        self.lines.iter().map(|line| line.minutes).sum()
+    }
+
+    pub fn billed_minutes(&self) -> i64 {
+        self.lines.iter().map(|line| line.billed_minutes).sum()
+    }
+
+    pub fn total_amount(&self) -> f64 {
+        (self.lines.iter().map(|line| line.amount).sum::<f64>() * 100.0).round() / 100.0
+    }
+
+    fn money(&self, amount: f64) -> String {
+        format!("{:.2} {}", amount, self.currency)
+    }
+
+    fn line_columns(&self, line: &InvoiceLine) -> [String; 6] {
+        [
+            line.date.format("%a %Y-%m-%d").to_string(),
+            line.project.clone(),
+            line.task.clone().unwrap_or_default(),
+            format_minutes(line.minutes),
+            format_minutes(line.billed_minutes),
+            self.money(line.amount),
+        ]
+    }
+
+    fn total_columns(&self) -> [String; 6] {
+        [
+            "Total".to_string(),
+            String::new(),
+            String::new(),
+            format_minutes(self.total_minutes()),
+            format_minutes(self.billed_minutes()),
+            self.money(self.total_amount()),
+        ]
+    }
+
+    const HEADER: [&'static str; 6] = ["Day", "Project", "Task", "Tracked", "Billed", "Amount"];
+
+    fn heading(&self) -> String {
+        format!("Invoice draft: {}, {}", self.client, self.title)
+    }
+
+    fn rate_line(&self) -> String {
+        format!("Rate: {}/h, {} to {}", self.money(self.rate), self.from, self.to)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n", self.heading());
+        let _ = writeln!(out, "{}\n", self.rate_line());
+        let _ = writeln!(out, "| {} |", Self::HEADER.join(" | "));
+        let _ = writeln!(out, "|---|---|---|---:|---:|---:|");
+        for line in &self.lines {
+            let columns = self.line_columns(line).map(|c| escape_markdown_cell(&c));
+            let _ = writeln!(out, "| {} |", columns.join(" | "));
+        }
+        let total = self.total_columns().map(|c| if c.is_empty() { c } else { format!("**{}**", c) });
+        let _ = writeln!(out, "| {} |", total.join(" | "));
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        let title = self.heading();
+        let _ = writeln!(out, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">");
+        let _ = writeln!(out, "<title>{}</title>", escape_html(&title));
+        let _ = writeln!(
+            out,
+            "<style>body {{ font-family: sans-serif; }} table {{ border-collapse: collapse; }} \
+             td, th {{ border: 1px solid #ccc; padding: 2px 8px; }} td:nth-child(n+4) {{ text-align: right; }}</style>"
+        );
+        let _ = writeln!(out, "</head>\n<body>\n<h1>{}</h1>", escape_html(&title));
+        let _ = writeln!(out, "<p>{}</p>\n<table>", escape_html(&self.rate_line()));
+        let _ = writeln!(out, "<tr><th>{}</th></tr>", Self::HEADER.join("</th><th>"));
+        for line in &self.lines {
+            let columns = self.line_columns(line).map(|c| escape_html(&c));
+            let _ = writeln!(out, "<tr><td>{}</td></tr>", columns.join("</td><td>"));
+        }
+        let _ = writeln!(out, "<tr><th>{}</th></tr>", self.total_columns().join("</th><th>"));
+        let _ = writeln!(out, "</table>\n</body>\n</html>");
+        out
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        let mut value = serde_json::to_value(self)?;
+        value["total_minutes"] = self.total_minutes().into();
+        value["billed_minutes"] = self.billed_minutes().into();
+        value["amount"] = self.total_amount().into();
+        Ok(serde_json::to_string_pretty(&value)? + "\n")
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc_time::{local_timestamp, parse_time};
+    use crate::config::{Client, Project, Rounding};
+    use crate::model::{MeetingList, ProjectTime, Timestamp};
+
+    fn at(date: &str, time: &str) -> Timestamp {
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+        local_timestamp(date, parse_time(time).unwrap())
+    }
+
+    fn session(date: &str, project: &str, task: Option<&str>, start: &str, end: &str) -> ProjectTime {
+        ProjectTime {
+            project: project.to_string(),
+            task: task.map(String::from),
+            project_start_time: at(date, start),
+            project_end_time: at(date, end),
+            time_in_project: 0,
+        }
+    }
+
+    fn day(date: &str, meetings: Vec<MeetingList>, projects: Vec<ProjectTime>) -> Worktime {
+        Worktime::new(
+            date.to_string(),
+            at(date, "08:00"),
+            at(date, "18:00"),
+            meetings,
+            Vec::new(),
+            Vec::new(),
+            projects,
+        )
+    }
+
+    fn config() -> Config {
+        let project = |name: &str, client: &str| Project {
+            name: name.to_string(),
+            client: Some(client.to_string()),
+            ..Project::default()
+        };
+        Config {
+            projects: vec![project("Apollo", "Acme"), project("Gemini", "Other")],
+            clients: vec![Client {
+                name: "Acme".to_string(),
+                rate: 90.0,
+                increment: 15,
+                rounding: Rounding::Up,
+                ..Client::default()
+            }],
+            ..Config::default()
+        }
+    }
+
+    fn may() -> Period {
+        Period::Month { year: 2024, month: 5 }
+    }
+
+    #[test]
+    fn lines_are_rounded_one_by_one() {
+        let internal = MeetingList {
+            meeting_name: "retro".to_string(),
+            meeting_start_time: at("2024-05-06", "13:00"),
+            meeting_end_time: at("2024-05-06", "13:30"),
+            time_in_meeting: 30,
+            project: Some("Apollo".to_string()),
+            billable: false,
+            notes: String::new(),
+            action_items: Vec::new(),
+            pauses: Vec::new(),
+        };
+        let worktime = vec![
+            day(
+                "2024-05-06",
+                vec![internal],
+                vec![
+                    session("2024-05-06", "Apollo", Some("design"), "09:00", "09:50"),
+                    session("2024-05-06", "Apollo", Some("review"), "10:00", "10:20"),
+                    session("2024-05-06", "Gemini", None, "11:00", "12:00"),
+                ],
+            ),
+            day("2024-05-07", Vec::new(), vec![session("2024-05-07", "Apollo", Some("design"), "09:00", "10:00")]),
+            day("2024-06-03", Vec::new(), vec![session("2024-06-03", "Apollo", Some("design"), "09:00", "10:00")]),
+        ];
+        let invoice = Invoice::build(&worktime, &config(), "Acme", may()).unwrap();
+
+        let lines: Vec<(String, Option<&str>, i64, i64, f64)> = invoice
+            .lines
+            .iter()
+            .map(|line| (line.date.to_string(), line.task.as_deref(), line.minutes, line.billed_minutes, line.amount))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                ("2024-05-06".to_string(), Some("design"), 50, 60, 90.0),
+                ("2024-05-06".to_string(), Some("review"), 20, 30, 45.0),
+                ("2024-05-07".to_string(), Some("design"), 60, 60, 90.0),
+            ]
+        );
+        assert!(invoice.lines.iter().all(|line| line.project == "Apollo"));
+        assert_eq!(invoice.total_minutes(), 130);
+        // rounding the 130 minutes as a whole would bill 135
+        assert_eq!(invoice.billed_minutes(), 150);
+        assert_eq!(invoice.total_amount(), 225.0);
+        assert_eq!(invoice.from, NaiveDate::from_ymd_opt(2024, 5, 1).unwrap());
+        assert_eq!(invoice.to, NaiveDate::from_ymd_opt(2024, 5, 31).unwrap());
+    }
+
+    #[test]
+    fn unknown_clients_and_clients_without_projects_fail() {
+        assert!(Invoice::build(&[], &config(), "Nobody", may()).is_err());
+        let mut config = config();
+        config.projects.clear();
+        assert!(Invoice::build(&[], &config, "Acme", may()).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+use anyhow::Result;
+use crate::calc_time::now;
+use crate::model::{billable_default, is_billable, ActionItem, Timestamp};
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use serde_jsonlines::json_lines;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_JOURNAL_PATH: &str = "data/journal.jsonl";
+
+/// A state change of the day that is still in progress.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum JournalEvent {
+    StartSet { time: Timestamp },
+    EndSet { time: Timestamp },
+    MeetingStarted {
+        name: String,
+        time: Timestamp,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        project: Option<String>,
+        #[serde(default = "billable_default", skip_serializing_if = "is_billable")]
+        billable: bool,
+    },
+    MeetingPaused { time: Timestamp },
+    MeetingResumed { time: Timestamp },
+    MeetingEnded { time: Timestamp, minutes: i32 },
+    /// A finished meeting taken over from the calendar.
+    MeetingAdded { name: String, start: Timestamp, end: Timestamp, minutes: i32 },
+    FocusStarted {
+        time: Timestamp,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        project: Option<String>,
+        #[serde(default = "billable_default", skip_serializing_if = "is_billable")]
+        billable: bool,
+    },
+    FocusPaused { time: Timestamp },
+    FocusResumed { time: Timestamp },
+    FocusEnded { time: Timestamp, minutes: u64 },
+    /// The running meeting and focus session were marked (non-)billable.
+    BillableSet { billable: bool },
+    /// Notes of the running meeting (`meeting` is `None`) or of the meeting
+    /// at that index in today's list.
+    MeetingNotesSaved {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        meeting: Option<usize>,
+        notes: String,
+    },
+    /// An action item for the running meeting or one of today's, as above.
+    ActionItemAdded {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        meeting: Option<usize>,
+        item: ActionItem,
+    },
+    /// The action item at `item` of that meeting was marked (not) done.
+    ActionItemDone {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        meeting: Option<usize>,
+        item: usize,
+        done: bool,
+    },
+    BreakStarted { time: Timestamp },
+    BreakEnded { time: Timestamp, minutes: i32 },
+    ProjectStarted {
+        project: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        task: Option<String>,
+        time: Timestamp,
+    },
+    ProjectEnded { time: Timestamp, minutes: i32 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub date: String,
+    pub recorded_at: Timestamp,
+    #[serde(flatten)]
+    pub event: JournalEvent,
+}
+
+/// Append-only log of the current day, written as events happen so that a
+/// crash or a closed terminal does not lose anything that is not exported yet.
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn open_default() -> Self {
+        Self::new(DEFAULT_JOURNAL_PATH)
+    }
+
+    pub fn record(&self, event: JournalEvent) -> Result<()> {
+        let recorded_at = now();
+        let entry = JournalEntry {
+            date: recorded_at.format("%Y-%m-%d").to_string(),
+            recorded_at,
+            event,
+        };
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    /// Record `event` in place of the last entry if `supersedes` says that
+    /// one is obsolete now, as notes saved again are. The file is replaced
+    /// as a whole, so a crash keeps either the old or the new entry.
+    pub fn record_superseding(&self, event: JournalEvent, supersedes: impl Fn(&JournalEvent) -> bool) -> Result<()> {
+        let text = match std::fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let kept = text.trim_end_matches('\n');
+        let last_start = kept.rfind('\n').map_or(0, |i| i + 1);
+        let obsolete = serde_json::from_str::<JournalEntry>(&kept[last_start..])
+            .is_ok_and(|last| supersedes(&last.event));
+        if !obsolete {
+            return self.record(event);
+        }
+        let recorded_at = now();
+        let entry = JournalEntry {
+            date: recorded_at.format("%Y-%m-%d").to_string(),
+            recorded_at,
+            event,
+        };
+        let tmp = self.path.with_extension("jsonl.tmp");
+        let mut file = std::fs::File::create(&tmp)?;
+        write!(file, "{}", &kept[..last_start])?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        file.sync_data()?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// All recorded entries. A torn last line from a crash mid-write is skipped.
+    pub fn entries(&self) -> Result<Vec<JournalEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let entries = json_lines::<JournalEntry, _>(&self.path)?
+            .filter_map(|entry| entry.ok())
+            .collect();
+        Ok(entries)
+    }
+
+    /// Whether the journalled day is still going on rather than a day that was
+    /// never exported. A night shift that started yesterday is still running.
+    pub fn is_current(entries: &[JournalEntry]) -> bool {
+        let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+            return false;
+        };
+        let today = now().format("%Y-%m-%d").to_string();
+        first.date == today || now() - last.recorded_at < Duration::hours(12)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries().map(|e| e.is_empty()).unwrap_or(true)
+    }
+
+    /// Forget the recorded day, e.g. after it has been exported.
+    pub fn clear(&self) -> Result<()> {
+        if self.path.exists() {
+            std::fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+}
@@ -1,9 +1,25 @@
 pub mod app;
 pub mod calc_time;
+pub mod cli;
+pub mod config;
+pub mod control;
+pub mod export_csv;
+pub mod export_ics;
 pub mod export_json;
+pub mod export_notes;
+pub mod interop;
+pub mod invoice;
+pub mod journal;
+pub mod model;
 pub mod read_json;
+pub mod report;
+pub mod status;
 pub mod tui;
 pub mod ui;
+pub mod calendar;
+pub mod balance;
 pub mod barchart;
 pub mod tabs;
 pub mod list;
+pub mod store;
+pub mod timesheet;
@@ -2,9 +2,15 @@ use crossterm::event::{self, KeyCode, KeyEventKind};
 use std::io::Result;
 use time_tracking_basic::app::{App, CurrentScreen, CurrentlyEditing, Tab};
 use time_tracking_basic::tui::{Event, Tui};
+use time_tracking_basic::tabs::NotesView;
 use time_tracking_basic::ui::ui;
-use time_tracking_basic::calc_time::parse_time;
+use time_tracking_basic::calc_time::{minutes_between, now};
+use time_tracking_basic::cli;
+use time_tracking_basic::control::{self, ControlMessage, DEFAULT_SOCKET_PATH};
+use time_tracking_basic::config::{Config, DEFAULT_CONFIG_PATH};
+use time_tracking_basic::journal::Journal;
 use std::io::prelude::*;
+use tokio::sync::mpsc::UnboundedReceiver;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -12,8 +18,36 @@ async fn main() -> Result<()> {
     if !std::path::Path::new("data").exists() {
         std::fs::create_dir_all("data")?;
     }
-    if !std::path::Path::new(".tmp_cache").exists() {
-        std::fs::create_dir_all(".tmp_cache")?;
+
+    if let Err(e) = Config::create_default(DEFAULT_CONFIG_PATH) {
+        eprintln!("Error writing the default config: {}", e);
+    }
+    let config = match Config::load_default() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error reading {}: {:#}", DEFAULT_CONFIG_PATH, e);
+            std::process::exit(1);
+        }
+    };
+
+    // with a subcommand, run headless instead of starting the TUI
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(e) = cli::run(&args, config) {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // only one TUI at a time; others talk to it through the control socket
+    std::fs::create_dir_all(".tmp_cache")?;
+    if control::is_running(DEFAULT_SOCKET_PATH) {
+        eprintln!("Error: another instance is already running ({})", DEFAULT_SOCKET_PATH);
+        std::process::exit(1);
+    }
+
+    if !std::path::Path::new(".tmp_cache/focus_cache.bin").exists() {
         std::fs::File::create(".tmp_cache/focus_cache.bin")?;
         let mut focus_cache_file = std::fs::File::create(".tmp_cache/focus_cache.bin").unwrap();
         let export_focus: String = "false".to_string() + &','.to_string() + "0";
@@ -30,58 +64,96 @@ async fn main() -> Result<()> {
         list_cache_file.write_all(export.as_bytes()).unwrap();
     }
 
+    let mut app = App::with_config(config);
+    restore_unfinished_day(&mut app)?;
+
+    // bound after the restore prompt, so clients do not hang on it
+    let control_rx = match control::listen(DEFAULT_SOCKET_PATH) {
+        Ok(control_rx) => control_rx,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(1);
+        }
+    };
+
     // create app and run it
-    let res = run_app().await;
+    let res = run_app(app, control_rx).await;
+    let _ = std::fs::remove_file(DEFAULT_SOCKET_PATH);
     res?;
     Ok(())
 }
 
-async fn run_app() -> Result<()> {
+fn confirm(question: &str) -> Result<bool> {
+    print!("{} (y/n) ", question);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("y"))
+}
+
+fn restore_unfinished_day(app: &mut App) -> Result<()> {
+    let journal = Journal::open_default();
+    let entries = journal.entries().unwrap_or_default();
+    let Some(first) = entries.first() else {
+        return Ok(());
+    };
+
+    if Journal::is_current(&entries) {
+        if confirm("Restore the unfinished day from the journal?")? {
+            app.restore_from_journal(&entries, now());
+            app.chache_focus_time();
+        } else {
+            journal.clear().map_err(std::io::Error::other)?;
+        }
+    } else if confirm(&format!(
+        "Found the unfinished day {} in the journal. Save it to the history?",
+        first.date
+    ))? {
+        App::export_unfinished(app.config.clone(), &entries).map_err(std::io::Error::other)?;
+        app.reload_history();
+    } else {
+        journal.clear().map_err(std::io::Error::other)?;
+    }
+    Ok(())
+}
+
+async fn run_app(mut app: App, mut control_rx: UnboundedReceiver<ControlMessage>) -> Result<()> {
     let mut tui = Tui::new()?;
     tui.enter()?;
 
-    let mut app = App::new();
-    let mut counter = 0;
-    let mut counter_focus = 0;
     loop {
-        let event = tui.next().await?;
+        let event = tokio::select! {
+            event = tui.next() => event?,
+            Some((request, reply)) = control_rx.recv() => {
+                let _ = reply.send(control::apply(&mut app, request));
+                app.chache_focus_time();
+                continue;
+            }
+        };
         if let Event::Render = event.clone() {
             tui.draw(|f| ui(f, &mut app))?;
         };
 
         if let Event::Tick = event.clone() {
+            // paused sessions do not count
             if app.meeting_running {
-                counter += 1;
-                if counter == 60 {
-                    app.time_in_meetings += 1;
-                    counter = 0;
-                }
+                app.time_in_meetings = app.meeting_minutes(now()) as i32;
             }
             if app.focus {
-                counter_focus += 1;
-                if counter_focus == 60 {
-                    app.focus_time += 1;
+                let focus_time = app.focus_minutes(now()) as u64;
+                if focus_time != app.focus_time {
+                    app.focus_time = focus_time;
                     app.chache_focus_time();
-                    counter_focus = 0;
                 }
             }
-            let current_time = chrono::Local::now().format("%H:%M").to_string();
-            if app.starttime_pairs.is_empty() {
-                let start_time = parse_time(&app.default_starttime);
-                let start_minutes = start_time.0 * 60 + start_time.1;
-                let current_time = parse_time(&current_time);
-                let current_minutes = current_time.0 * 60 + current_time.1;
-                let current_worktime = current_minutes - start_minutes;
-                app.current_worktime = current_worktime as u64
-            } 
-            if app.starttime_pairs.contains_key(&app.starttime_key) {
-                let start_time = parse_time(&app.starttime_pairs[&app.starttime_key]);
-                let start_minutes = start_time.0 * 60 + start_time.1;
-                let current_time = parse_time(&current_time);
-                let current_minutes = current_time.0 * 60 + current_time.1;
-                let current_worktime = current_minutes - start_minutes;
-                app.current_worktime = current_worktime as u64
+            if app.break_running {
+                app.time_in_break = minutes_between(app.break_start_time, now()) as i32;
+            }
+            if app.project_running {
+                app.time_in_project = minutes_between(app.project_start_time, now()) as i32;
             }
+            app.autosave_notes();
+            app.current_worktime = app.worked_minutes(now()).max(0) as u64;
 
             app.total_time_in_meetings = app.meeting_list
                 .clone()
@@ -113,12 +185,60 @@ async fn run_app() -> Result<()> {
                     KeyCode::Char('m') => {
                         app.current_screen = CurrentScreen::EditingMeetingName;
                         app.currently_editing = Some(CurrentlyEditing::MeetingName);
+                        app.load_meeting_suggestions();
+                    }
+
+                    KeyCode::Char('i') => {
+                        app.import_todays_meetings();
                     }
 
                     KeyCode::Char('M') => {
                         app.end_meeting();
                     }
 
+                    KeyCode::Char('x') => {
+                        app.prefill_export_range();
+                        app.current_screen = CurrentScreen::ExportingCsv;
+                        app.currently_editing = Some(CurrentlyEditing::ExportRange);
+                    }
+
+                    KeyCode::Char('b') => {
+                        app.start_break();
+                    }
+
+                    KeyCode::Char('B') => {
+                        app.end_break();
+                    }
+
+                    KeyCode::Char('t') => {
+                        app.current_screen = CurrentScreen::EditingProject;
+                        app.currently_editing = Some(CurrentlyEditing::Project);
+                    }
+
+                    KeyCode::Char('T') => {
+                        app.end_project();
+                    }
+
+                    // the focus tab pauses its session, elsewhere a running
+                    // meeting comes first
+                    KeyCode::Char('P') => {
+                        match app.tab {
+                            Tab::FocusTimeTab => {
+                                app.toggle_focus_pause();
+                            }
+                            _ if app.meeting_running => {
+                                app.toggle_meeting_pause();
+                            }
+                            _ => {
+                                app.toggle_focus_pause();
+                            }
+                        }
+                    }
+
+                    KeyCode::Char('$') => {
+                        app.toggle_billable();
+                    }
+
                     KeyCode::Char('q') => {
                         app.current_screen = CurrentScreen::Exiting;
                     }
@@ -153,8 +273,19 @@ async fn run_app() -> Result<()> {
                                 continue;
                             }
                             Tab::MeetingNotesTab => {
+                                let entries = app.note_entries();
+                                let len = match app.meeting_notes_tab.view {
+                                    NotesView::Meetings => entries.len(),
+                                    NotesView::ActionItems => app.meeting_notes_tab.action_items(&entries).len(),
+                                };
+                                app.meeting_notes_tab.next(len);
+                            }
+                            Tab::BalanceTab => {
                                 continue;
                             }
+                            Tab::ReportTab => {
+                                app.report_tab.next_period();
+                            }
                         }
                     }
 
@@ -170,6 +301,104 @@ async fn run_app() -> Result<()> {
                                 continue;
                             }
                             Tab::MeetingNotesTab => {
+                                app.meeting_notes_tab.previous();
+                            }
+                            Tab::BalanceTab => {
+                                continue;
+                            }
+                            Tab::ReportTab => {
+                                app.report_tab.previous_period();
+                            }
+                        }
+                    }
+
+                    KeyCode::Char('o') => {
+                        match app.tab {
+                            Tab::BarChartTab => {
+                                app.barchart_tab.toggle_split_overnight();
+                            }
+                            Tab::EditHistoryTab => {
+                                continue;
+                            }
+                            Tab::FocusTimeTab => {
+                                continue;
+                            }
+                            Tab::MeetingNotesTab => {
+                                app.meeting_notes_tab.toggle_view();
+                            }
+                            Tab::BalanceTab => {
+                                continue;
+                            }
+                            Tab::ReportTab => {
+                                continue;
+                            }
+                        }
+                    }
+
+                    KeyCode::Enter => {
+                        match app.tab {
+                            Tab::MeetingNotesTab if app.meeting_notes_tab.view == NotesView::Meetings => {
+                                app.open_notes();
+                            }
+                            _ => {
+                                continue;
+                            }
+                        }
+                    }
+
+                    KeyCode::Char('a') => {
+                        match app.tab {
+                            Tab::MeetingNotesTab => {
+                                app.begin_action_item();
+                            }
+                            _ => {
+                                continue;
+                            }
+                        }
+                    }
+
+                    KeyCode::Char('d') => {
+                        match app.tab {
+                            Tab::MeetingNotesTab if app.meeting_notes_tab.view == NotesView::ActionItems => {
+                                app.toggle_action_item();
+                            }
+                            _ => {
+                                continue;
+                            }
+                        }
+                    }
+
+                    KeyCode::Char('/') => {
+                        match app.tab {
+                            Tab::MeetingNotesTab => {
+                                app.begin_action_item_search();
+                            }
+                            _ => {
+                                continue;
+                            }
+                        }
+                    }
+
+                    KeyCode::Char('p') => {
+                        match app.tab {
+                            Tab::ReportTab => {
+                                app.report_tab.toggle_period();
+                            }
+                            _ => {
+                                continue;
+                            }
+                        }
+                    }
+
+                    KeyCode::Char('W') => {
+                        match app.tab {
+                            Tab::ReportTab => {
+                                app.write_report_files();
+                            }
+                            Tab::MeetingNotesTab => {
+                                app.write_note_files();
+                            }
+                            _ => {
                                 continue;
                             }
                         }
@@ -196,6 +425,12 @@ async fn run_app() -> Result<()> {
                             Tab::MeetingNotesTab => {
                                 continue;
                             }
+                            Tab::BalanceTab => {
+                                continue;
+                            }
+                            Tab::ReportTab => {
+                                continue;
+                            }
                         }
                     }
                     _ => {}
@@ -225,8 +460,9 @@ async fn run_app() -> Result<()> {
                     match key.code {
                         KeyCode::Enter => {
                             if let Some(CurrentlyEditing::Starttime) = &app.currently_editing {
-                                app.save_starttime_value();
-                                app.current_screen = CurrentScreen::Main;
+                                if app.save_starttime_value() {
+                                    app.current_screen = CurrentScreen::Main;
+                                }
                             }
                         }
 
@@ -253,8 +489,9 @@ async fn run_app() -> Result<()> {
                     match key.code {
                         KeyCode::Enter => {
                             if let Some(CurrentlyEditing::Endtime) = &app.currently_editing {
-                                app.save_endtime_value();
-                                app.current_screen = CurrentScreen::Main;
+                                if app.save_endtime_value() {
+                                    app.current_screen = CurrentScreen::Main;
+                                }
                             }
                         }
                         KeyCode::Backspace => {
@@ -278,8 +515,10 @@ async fn run_app() -> Result<()> {
                     match key.code {
                         KeyCode::Enter => {
                             if let Some(CurrentlyEditing::MeetingName) = &app.currently_editing {
+                                app.meeting_suggestions.clear();
                                 app.start_meeting();
                                 app.current_screen = CurrentScreen::Main;
+                                app.open_notes();
                             }
                         }
                         KeyCode::Backspace => {
@@ -290,6 +529,15 @@ async fn run_app() -> Result<()> {
                         KeyCode::Esc => {
                             app.current_screen = CurrentScreen::Main;
                             app.currently_editing = None;
+                            app.meeting_suggestions.clear();
+                        }
+                        // a digit on an empty name picks a calendar suggestion
+                        KeyCode::Char(value @ '1'..='9')
+                            if app.meeting_name_input.is_empty()
+                                && app.pick_meeting_suggestion(value as usize - '1' as usize) =>
+                        {
+                            app.current_screen = CurrentScreen::Main;
+                            app.open_notes();
                         }
                         KeyCode::Char(value) => {
                             if let Some(CurrentlyEditing::MeetingName) = &app.currently_editing {
@@ -299,6 +547,125 @@ async fn run_app() -> Result<()> {
                         _ => {}
                     }
                 }
+                CurrentScreen::EditingProject if key.kind == KeyEventKind::Press => {
+                    match key.code {
+                        KeyCode::Enter => {
+                            if let Some(CurrentlyEditing::Project) = &app.currently_editing {
+                                if app.start_project() {
+                                    app.current_screen = CurrentScreen::Main;
+                                }
+                            }
+                        }
+                        KeyCode::Backspace => {
+                            if let Some(CurrentlyEditing::Project) = &app.currently_editing {
+                                app.project_input.pop();
+                            }
+                        }
+                        KeyCode::Esc => {
+                            app.current_screen = CurrentScreen::Main;
+                            app.currently_editing = None;
+                            app.project_input = String::new();
+                        }
+                        // a digit on an empty input picks a configured project
+                        KeyCode::Char(value @ '1'..='9')
+                            if app.project_input.is_empty() && app.pick_project(value as usize - '1' as usize) =>
+                        {
+                            app.current_screen = CurrentScreen::Main;
+                        }
+                        KeyCode::Char(value) => {
+                            if let Some(CurrentlyEditing::Project) = &app.currently_editing {
+                                app.project_input.push(value);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                CurrentScreen::EditingNotes if key.kind == KeyEventKind::Press => {
+                    match key.code {
+                        KeyCode::Esc => {
+                            app.close_notes();
+                        }
+                        code => {
+                            app.edit_notes(code);
+                        }
+                    }
+                }
+                CurrentScreen::AddingActionItem if key.kind == KeyEventKind::Press => {
+                    match key.code {
+                        KeyCode::Enter => {
+                            if let Some(CurrentlyEditing::ActionItem) = &app.currently_editing {
+                                if app.add_action_item() {
+                                    app.current_screen = CurrentScreen::Main;
+                                }
+                            }
+                        }
+                        KeyCode::Backspace => {
+                            if let Some(CurrentlyEditing::ActionItem) = &app.currently_editing {
+                                app.action_item_input.pop();
+                            }
+                        }
+                        KeyCode::Esc => {
+                            app.current_screen = CurrentScreen::Main;
+                            app.currently_editing = None;
+                            app.action_item_input = String::new();
+                        }
+                        KeyCode::Char(value) => {
+                            if let Some(CurrentlyEditing::ActionItem) = &app.currently_editing {
+                                app.action_item_input.push(value);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                CurrentScreen::SearchingActionItems if key.kind == KeyEventKind::Press => {
+                    match key.code {
+                        KeyCode::Enter => {
+                            app.current_screen = CurrentScreen::Main;
+                            app.currently_editing = None;
+                        }
+                        KeyCode::Backspace => {
+                            app.meeting_notes_tab.search.pop();
+                            app.meeting_notes_tab.item_selected = 0;
+                        }
+                        KeyCode::Esc => {
+                            app.current_screen = CurrentScreen::Main;
+                            app.currently_editing = None;
+                            app.meeting_notes_tab.search = String::new();
+                            app.meeting_notes_tab.item_selected = 0;
+                        }
+                        KeyCode::Char(value) => {
+                            app.meeting_notes_tab.search.push(value);
+                            app.meeting_notes_tab.item_selected = 0;
+                        }
+                        _ => {}
+                    }
+                }
+                CurrentScreen::ExportingCsv if key.kind == KeyEventKind::Press => {
+                    match key.code {
+                        KeyCode::Enter => {
+                            if let Some(CurrentlyEditing::ExportRange) = &app.currently_editing {
+                                if app.save_export_csv() {
+                                    app.current_screen = CurrentScreen::Main;
+                                }
+                            }
+                        }
+                        KeyCode::Backspace => {
+                            if let Some(CurrentlyEditing::ExportRange) = &app.currently_editing {
+                                app.export_range_input.pop();
+                            }
+                        }
+                        KeyCode::Esc => {
+                            app.current_screen = CurrentScreen::Main;
+                            app.currently_editing = None;
+                        }
+                        KeyCode::Char(value) => {
+                            if let Some(CurrentlyEditing::ExportRange) = &app.currently_editing {
+                                app.export_range_input.push(value);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
 
                 _ => {}
             }
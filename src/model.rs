@@ -0,0 +1,876 @@
+use crate::calc_time::{legacy_timestamp, minutes_between};
+use crate::config::BreakRules;
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Version written into every `Worktime` record. Bump it together with a new
+/// step in `upgrade` whenever the stored shape changes.
+pub const CURRENT_SCHEMA_VERSION: u32 = 8;
+
+/// A point in time with the UTC offset that was in effect locally, stored as
+/// RFC 3339.
+pub type Timestamp = DateTime<FixedOffset>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeetingList {
+    pub meeting_name: String,
+    pub meeting_start_time: Timestamp,
+    pub meeting_end_time: Timestamp,
+    pub time_in_meeting: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default = "billable_default", skip_serializing_if = "is_billable")]
+    pub billable: bool,
+    /// Free text taken in the meeting notes tab.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub action_items: Vec<ActionItem>,
+    /// Interruptions, not counted in `time_in_meeting`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pauses: Vec<Pause>,
+}
+
+/// A stretch in which a meeting or focus session was paused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pause {
+    pub start: Timestamp,
+    pub end: Timestamp,
+}
+
+/// Minutes of `pauses`, plus those of a pause still running since `open`
+/// until `at`.
+pub fn paused_minutes(pauses: &[Pause], open: Option<Timestamp>, at: Timestamp) -> i64 {
+    pauses
+        .iter()
+        .map(|pause| minutes_between(pause.start, pause.end))
+        .chain(open.map(|start| minutes_between(start, at)))
+        .sum()
+}
+
+/// `start..end` cut at `pauses`: the stretches in which the session ran.
+pub fn unpaused_spans(start: Timestamp, end: Timestamp, pauses: &[Pause]) -> Vec<(Timestamp, Timestamp)> {
+    let mut pauses: Vec<Pause> = pauses.to_vec();
+    pauses.sort_by_key(|pause| pause.start);
+    let mut spans = Vec::new();
+    let mut from = start;
+    for pause in pauses {
+        if pause.start > from {
+            spans.push((from, pause.start.min(end)));
+        }
+        from = from.max(pause.end);
+    }
+    if end > from {
+        spans.push((from, end));
+    }
+    spans
+}
+
+/// A follow-up agreed on in a meeting.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActionItem {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub done: bool,
+}
+
+impl ActionItem {
+    /// Read "TEXT [@OWNER] [due:YYYY-MM-DD]"; owner and due date may stand
+    /// anywhere between the words of the text.
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut words = Vec::new();
+        let mut owner = None;
+        let mut due = None;
+        for word in input.split_whitespace() {
+            if let Some(name) = word.strip_prefix('@').filter(|name| !name.is_empty()) {
+                owner = Some(name.to_string());
+            } else if let Some(date) = word.strip_prefix("due:") {
+                due = Some(
+                    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                        .map_err(|_| anyhow!("due date {:?} is not YYYY-MM-DD", date))?,
+                );
+            } else {
+                words.push(word);
+            }
+        }
+        if words.is_empty() {
+            bail!("an action item needs a text");
+        }
+        Ok(Self {
+            text: words.join(" "),
+            owner,
+            due,
+            done: false,
+        })
+    }
+
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.done && self.due.is_some_and(|due| due < today)
+    }
+
+    /// Whether the text or the owner contains `query`, ignoring case.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.text.to_lowercase().contains(&query)
+            || self.owner.as_ref().is_some_and(|owner| owner.to_lowercase().contains(&query))
+    }
+
+    /// "(@owner, due YYYY-MM-DD)" after the text, as far as they are set.
+    pub fn describe(&self) -> String {
+        let details: Vec<String> = self
+            .owner
+            .iter()
+            .map(|owner| format!("@{}", owner))
+            .chain(self.due.iter().map(|due| format!("due {}", due)))
+            .collect();
+        if details.is_empty() {
+            self.text.clone()
+        } else {
+            format!("{} ({})", self.text, details.join(", "))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FocusTime {
+    pub focus_time_start: Timestamp,
+    pub focus_time_end: Timestamp,
+    pub focus_time: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default = "billable_default", skip_serializing_if = "is_billable")]
+    pub billable: bool,
+    /// Interruptions, not counted in `focus_time`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pauses: Vec<Pause>,
+}
+
+// Meetings and focus sessions are billable unless marked otherwise; only the
+// exceptions are written.
+pub(crate) fn billable_default() -> bool {
+    true
+}
+
+pub(crate) fn is_billable(billable: &bool) -> bool {
+    *billable
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BreakTime {
+    pub break_start_time: Timestamp,
+    pub break_end_time: Timestamp,
+    pub time_in_break: i32,
+}
+
+/// Time booked to a project, and optionally to one of its tasks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectTime {
+    pub project: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    pub project_start_time: Timestamp,
+    pub project_end_time: Timestamp,
+    pub time_in_project: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Worktime {
+    pub schema_version: u32,
+    pub date: String,
+    pub starttime: Timestamp,
+    pub endtime: Timestamp,
+    pub meetings: Vec<MeetingList>,
+    pub focus_time: Vec<FocusTime>,
+    pub breaks: Vec<BreakTime>,
+    pub projects: Vec<ProjectTime>,
+}
+
+impl Worktime {
+    pub fn new(
+        date: String,
+        starttime: Timestamp,
+        endtime: Timestamp,
+        meetings: Vec<MeetingList>,
+        focus_time: Vec<FocusTime>,
+        breaks: Vec<BreakTime>,
+        projects: Vec<ProjectTime>,
+    ) -> Self {
+        Worktime {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            date,
+            starttime,
+            endtime,
+            meetings,
+            focus_time,
+            breaks,
+            projects,
+        }
+    }
+
+    /// Minutes from start to end, breaks included.
+    pub fn gross_minutes(&self) -> i64 {
+        minutes_between(self.starttime, self.endtime)
+    }
+
+    pub fn break_minutes(&self) -> i64 {
+        self.breaks.iter().map(|b| i64::from(b.time_in_break.max(0))).sum()
+    }
+
+    /// Worked minutes: gross time minus the recorded breaks and minus whatever
+    /// the break rules require on top of them.
+    pub fn worked_minutes(&self, rules: &BreakRules) -> i64 {
+        let without_breaks = self.gross_minutes() - self.break_minutes();
+        without_breaks - rules.missing_break(without_breaks, self.break_minutes())
+    }
+
+    /// Minutes per project. Project sessions count without the breaks taken
+    /// during them; meetings and focus sessions tagged with a project count
+    /// for that project, also where they lie in the session of another one,
+    /// which loses that time. No minute counts twice.
+    pub fn project_minutes(&self) -> BTreeMap<String, i64> {
+        let mut totals: BTreeMap<String, i64> = BTreeMap::new();
+        for ((project, _), minutes) in self.task_minutes(false) {
+            *totals.entry(project).or_default() += minutes;
+        }
+        totals
+    }
+
+    /// Minutes per project and task, counted as in [`Self::project_minutes`].
+    /// A meeting wins over a focus session at the same time. Tagged meetings
+    /// and focus sessions have no task unless they lie in a session of their
+    /// own project, and do not count while paused. With `billable_only` the
+    /// time of non-billable meetings and focus sessions counts for nobody.
+    pub fn task_minutes(&self, billable_only: bool) -> BTreeMap<(String, Option<String>), i64> {
+        struct Activity<'a> {
+            start: Timestamp,
+            end: Timestamp,
+            project: Option<&'a String>,
+            billable: bool,
+            pauses: &'a [Pause],
+        }
+        // meetings first, so they win over focus sessions
+        let activities: Vec<Activity> = self
+            .meetings
+            .iter()
+            .map(|m| Activity {
+                start: m.meeting_start_time,
+                end: m.meeting_end_time,
+                project: m.project.as_ref(),
+                billable: m.billable,
+                pauses: &m.pauses,
+            })
+            .chain(self.focus_time.iter().map(|f| Activity {
+                start: f.focus_time_start,
+                end: f.focus_time_end,
+                project: f.project.as_ref(),
+                billable: f.billable,
+                pauses: &f.pauses,
+            }))
+            .collect();
+
+        let mut bounds: Vec<Timestamp> = self
+            .projects
+            .iter()
+            .flat_map(|p| [p.project_start_time, p.project_end_time])
+            .chain(self.breaks.iter().flat_map(|b| [b.break_start_time, b.break_end_time]))
+            .chain(activities.iter().flat_map(|a| {
+                [a.start, a.end].into_iter().chain(a.pauses.iter().flat_map(|p| [p.start, p.end]))
+            }))
+            .collect();
+        bounds.sort();
+        bounds.dedup();
+
+        let mut booked: BTreeMap<(String, Option<String>), Duration> = BTreeMap::new();
+        for pair in bounds.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            let within = |start: Timestamp, end: Timestamp| start <= from && to <= end;
+            if self.breaks.iter().any(|b| within(b.break_start_time, b.break_end_time)) {
+                continue;
+            }
+            let activity = activities
+                .iter()
+                .find(|a| within(a.start, a.end) && !a.pauses.iter().any(|p| within(p.start, p.end)));
+            if billable_only && activity.is_some_and(|a| !a.billable) {
+                continue;
+            }
+            let session = self
+                .projects
+                .iter()
+                .find(|p| within(p.project_start_time, p.project_end_time));
+            let key = match (activity.and_then(|a| a.project), session) {
+                (Some(project), Some(session)) if *project == session.project => {
+                    (session.project.clone(), session.task.clone())
+                }
+                (Some(project), _) => (project.clone(), None),
+                (None, Some(session)) => (session.project.clone(), session.task.clone()),
+                (None, None) => continue,
+            };
+            *booked.entry(key).or_insert_with(Duration::zero) += to - from;
+        }
+
+        // every project session is listed, even if other work took all its time
+        let mut totals: BTreeMap<(String, Option<String>), i64> = self
+            .projects
+            .iter()
+            .map(|p| ((p.project.clone(), p.task.clone()), 0))
+            .collect();
+        for (key, duration) in booked {
+            if duration.num_minutes() > 0 {
+                *totals.entry(key).or_default() += duration.num_minutes();
+            }
+        }
+        totals
+    }
+
+    /// Fold another record of the same date into this one: the earliest start
+    /// and the latest end win, meetings and focus sessions are concatenated.
+    pub fn merge(&mut self, other: Worktime) {
+        self.starttime = self.starttime.min(other.starttime);
+        self.endtime = self.endtime.max(other.endtime);
+        self.meetings.extend(other.meetings);
+        self.focus_time.extend(other.focus_time);
+        self.breaks.extend(other.breaks);
+        self.projects.extend(other.projects);
+    }
+}
+
+/// Collapse records sharing a date into one, keeping the first position.
+pub fn merge_duplicate_days(days: Vec<Worktime>) -> Vec<Worktime> {
+    let mut merged: Vec<Worktime> = Vec::with_capacity(days.len());
+    for day in days {
+        match merged.iter_mut().find(|d| d.date == day.date) {
+            Some(existing) => existing.merge(day),
+            None => merged.push(day),
+        }
+    }
+    merged
+}
+
+/// Bring a stored record of any known schema version up to the current one.
+/// Records written before versioning existed count as version 0.
+pub fn upgrade(mut record: Value) -> Result<Worktime> {
+    let mut version = record
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0) as u32;
+    if version > CURRENT_SCHEMA_VERSION {
+        bail!(
+            "record has schema version {}, this build only knows up to {}",
+            version,
+            CURRENT_SCHEMA_VERSION
+        );
+    }
+    while version < CURRENT_SCHEMA_VERSION {
+        record = match version {
+            0 => upgrade_v0(record),
+            1 => upgrade_v1(record)?,
+            2 => upgrade_v2(record),
+            3 => upgrade_v3(record),
+            4 => upgrade_v4(record),
+            5 => upgrade_v5(record),
+            6 => upgrade_v6(record),
+            7 => upgrade_v7(record),
+            _ => unreachable!(),
+        };
+        version += 1;
+    }
+    Ok(serde_json::from_value(record)?)
+}
+
+// v0 -> v1: the version field itself; meetings and focus sessions may be
+// missing in hand-written lines.
+fn upgrade_v0(mut record: Value) -> Value {
+    if let Some(object) = record.as_object_mut() {
+        object.insert("schema_version".to_string(), Value::from(1));
+        object.entry("meetings").or_insert_with(|| Value::Array(Vec::new()));
+        object.entry("focus_time").or_insert_with(|| Value::Array(Vec::new()));
+    }
+    record
+}
+
+// v1 -> v2: "HH:MM" strings become RFC 3339 timestamps on the record's date.
+fn upgrade_v1(mut record: Value) -> Result<Value> {
+    let date = record
+        .get("date")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("record without a date"))?
+        .to_string();
+
+    let object = record
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("record is not a JSON object"))?;
+    object.insert("schema_version".to_string(), Value::from(2));
+    // anything earlier than the start happened after midnight
+    let start = Some(upgrade_time_field(object, "starttime", &date, None)?);
+    upgrade_time_field(object, "endtime", &date, start)?;
+    for meeting in object["meetings"].as_array_mut().into_iter().flatten() {
+        if let Some(meeting) = meeting.as_object_mut() {
+            upgrade_time_field(meeting, "meeting_start_time", &date, start)?;
+            upgrade_time_field(meeting, "meeting_end_time", &date, start)?;
+        }
+    }
+    for focus in object["focus_time"].as_array_mut().into_iter().flatten() {
+        if let Some(focus) = focus.as_object_mut() {
+            upgrade_time_field(focus, "focus_time_start", &date, start)?;
+            upgrade_time_field(focus, "focus_time_end", &date, start)?;
+        }
+    }
+    Ok(record)
+}
+
+fn upgrade_time_field(
+    object: &mut serde_json::Map<String, Value>,
+    field: &str,
+    date: &str,
+    day_start: Option<Timestamp>,
+) -> Result<Timestamp> {
+    let legacy = object
+        .get(field)
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("missing {}", field))?;
+    let timestamp = legacy_timestamp(date, legacy, day_start)
+        .ok_or_else(|| anyhow!("invalid {} {:?} on {}", field, legacy, date))?;
+    object.insert(field.to_string(), Value::from(timestamp.to_rfc3339()));
+    Ok(timestamp)
+}
+
+// v2 -> v3: breaks; older days had none recorded.
+fn upgrade_v2(mut record: Value) -> Value {
+    if let Some(object) = record.as_object_mut() {
+        object.insert("schema_version".to_string(), Value::from(3));
+        object.entry("breaks").or_insert_with(|| Value::Array(Vec::new()));
+    }
+    record
+}
+
+// v3 -> v4: project sessions; meetings and focus sessions gain an optional
+// project that older records leave out.
+fn upgrade_v3(mut record: Value) -> Value {
+    if let Some(object) = record.as_object_mut() {
+        object.insert("schema_version".to_string(), Value::from(4));
+        object.entry("projects").or_insert_with(|| Value::Array(Vec::new()));
+    }
+    record
+}
+
+// v4 -> v5: meetings and focus sessions gain a billable flag. Everything
+// recorded before was billable, which is what a missing flag means.
+fn upgrade_v4(mut record: Value) -> Value {
+    if let Some(object) = record.as_object_mut() {
+        object.insert("schema_version".to_string(), Value::from(5));
+    }
+    record
+}
+
+// v5 -> v6: meetings gain notes; older meetings have none.
+fn upgrade_v5(mut record: Value) -> Value {
+    if let Some(object) = record.as_object_mut() {
+        object.insert("schema_version".to_string(), Value::from(6));
+    }
+    record
+}
+
+// v6 -> v7: meetings gain action items; older meetings have none.
+fn upgrade_v6(mut record: Value) -> Value {
+    if let Some(object) = record.as_object_mut() {
+        object.insert("schema_version".to_string(), Value::from(7));
+    }
+    record
+}
+
+// v7 -> v8: meetings and focus sessions gain pauses; older ones had none.
+fn upgrade_v7(mut record: Value) -> Value {
+    if let Some(object) = record.as_object_mut() {
+        object.insert("schema_version".to_string(), Value::from(8));
+    }
+    record
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc_time::{local_timestamp, parse_time};
+    use serde_json::json;
+
+    fn at(date: &str, time: &str) -> Timestamp {
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+        local_timestamp(date, parse_time(time).unwrap())
+    }
+
+    fn version(record: &Value) -> u64 {
+        record["schema_version"].as_u64().unwrap()
+    }
+
+    fn current_day() -> Worktime {
+        Worktime::new(
+            "2024-05-06".to_string(),
+            at("2024-05-06", "08:00"),
+            at("2024-05-06", "17:00"),
+            vec![MeetingList {
+                meeting_name: "Standup".to_string(),
+                meeting_start_time: at("2024-05-06", "09:00"),
+                meeting_end_time: at("2024-05-06", "09:30"),
+                time_in_meeting: 25,
+                project: Some("Apollo".to_string()),
+                billable: false,
+                notes: "went fine".to_string(),
+                action_items: vec![ActionItem::parse("send minutes @ana due:2024-05-07").unwrap()],
+                pauses: vec![Pause {
+                    start: at("2024-05-06", "09:10"),
+                    end: at("2024-05-06", "09:15"),
+                }],
+            }],
+            vec![FocusTime {
+                focus_time_start: at("2024-05-06", "10:00"),
+                focus_time_end: at("2024-05-06", "11:00"),
+                focus_time: 60,
+                project: None,
+                billable: true,
+                pauses: Vec::new(),
+            }],
+            vec![BreakTime {
+                break_start_time: at("2024-05-06", "12:00"),
+                break_end_time: at("2024-05-06", "12:30"),
+                time_in_break: 30,
+            }],
+            vec![ProjectTime {
+                project: "Apollo".to_string(),
+                task: Some("review".to_string()),
+                project_start_time: at("2024-05-06", "13:00"),
+                project_end_time: at("2024-05-06", "15:00"),
+                time_in_project: 120,
+            }],
+        )
+    }
+
+    #[test]
+    fn v0_gains_version_and_empty_sessions() {
+        let record = upgrade_v0(json!({"date": "2024-05-06", "starttime": "08:00", "endtime": "17:00"}));
+        assert_eq!(version(&record), 1);
+        assert_eq!(record["meetings"], json!([]));
+        assert_eq!(record["focus_time"], json!([]));
+    }
+
+    #[test]
+    fn v0_keeps_existing_sessions() {
+        let meetings = json!([{"meeting_name": "a", "meeting_start_time": "09:00",
+                               "meeting_end_time": "10:00", "time_in_meeting": 60}]);
+        let record = upgrade_v0(json!({"date": "2024-05-06", "meetings": meetings.clone()}));
+        assert_eq!(record["meetings"], meetings);
+    }
+
+    #[test]
+    fn v1_turns_times_into_timestamps() {
+        let record = upgrade_v1(json!({
+            "schema_version": 1,
+            "date": "2024-05-06",
+            "starttime": "08:00",
+            "endtime": "17:00",
+            "meetings": [{"meeting_name": "a", "meeting_start_time": "09:00",
+                          "meeting_end_time": "9:45", "time_in_meeting": 45}],
+            "focus_time": [{"focus_time_start": "10:00", "focus_time_end": "11:00", "focus_time": 60}],
+        }))
+        .unwrap();
+        assert_eq!(version(&record), 2);
+        let timestamp = |value: &Value| Timestamp::parse_from_rfc3339(value.as_str().unwrap()).unwrap();
+        assert_eq!(timestamp(&record["starttime"]), at("2024-05-06", "08:00"));
+        assert_eq!(timestamp(&record["endtime"]), at("2024-05-06", "17:00"));
+        assert_eq!(timestamp(&record["meetings"][0]["meeting_end_time"]), at("2024-05-06", "09:45"));
+        assert_eq!(timestamp(&record["focus_time"][0]["focus_time_start"]), at("2024-05-06", "10:00"));
+    }
+
+    #[test]
+    fn v1_moves_times_before_the_start_to_the_next_day() {
+        let record = upgrade_v1(json!({
+            "schema_version": 1,
+            "date": "2024-05-06",
+            "starttime": "22:00",
+            "endtime": "06:00",
+            "meetings": [{"meeting_name": "a", "meeting_start_time": "23:30",
+                          "meeting_end_time": "00:30", "time_in_meeting": 60}],
+            "focus_time": [],
+        }))
+        .unwrap();
+        let timestamp = |value: &Value| Timestamp::parse_from_rfc3339(value.as_str().unwrap()).unwrap();
+        assert_eq!(timestamp(&record["endtime"]), at("2024-05-07", "06:00"));
+        assert_eq!(timestamp(&record["meetings"][0]["meeting_start_time"]), at("2024-05-06", "23:30"));
+        assert_eq!(timestamp(&record["meetings"][0]["meeting_end_time"]), at("2024-05-07", "00:30"));
+    }
+
+    #[test]
+    fn v1_rejects_bad_records() {
+        let no_date = json!({"schema_version": 1, "starttime": "08:00", "endtime": "17:00"});
+        assert!(upgrade_v1(no_date).is_err());
+        let bad_time = json!({"schema_version": 1, "date": "2024-05-06", "starttime": "8 am",
+                              "endtime": "17:00", "meetings": [], "focus_time": []});
+        assert!(upgrade_v1(bad_time).is_err());
+    }
+
+    #[test]
+    fn v2_adds_breaks() {
+        let record = upgrade_v2(json!({"schema_version": 2}));
+        assert_eq!(version(&record), 3);
+        assert_eq!(record["breaks"], json!([]));
+    }
+
+    #[test]
+    fn v3_adds_projects() {
+        let record = upgrade_v3(json!({"schema_version": 3}));
+        assert_eq!(version(&record), 4);
+        assert_eq!(record["projects"], json!([]));
+    }
+
+    #[test]
+    fn v4_to_v7_only_bump_the_version() {
+        let steps: [fn(Value) -> Value; 4] = [upgrade_v4, upgrade_v5, upgrade_v6, upgrade_v7];
+        for (from, step) in (4..).zip(steps) {
+            let record = step(json!({"schema_version": from, "date": "2024-05-06"}));
+            assert_eq!(record, json!({"schema_version": from + 1, "date": "2024-05-06"}));
+        }
+    }
+
+    #[test]
+    fn v0_record_upgrades_to_current() {
+        let day = upgrade(json!({"date": "2024-05-06", "starttime": "08:00", "endtime": "17:00"})).unwrap();
+        assert_eq!(
+            day,
+            Worktime::new(
+                "2024-05-06".to_string(),
+                at("2024-05-06", "08:00"),
+                at("2024-05-06", "17:00"),
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+            )
+        );
+    }
+
+    #[test]
+    fn v4_sessions_get_the_later_defaults() {
+        let day = upgrade(json!({
+            "schema_version": 4,
+            "date": "2024-05-06",
+            "starttime": at("2024-05-06", "08:00"),
+            "endtime": at("2024-05-06", "17:00"),
+            "meetings": [{"meeting_name": "a",
+                          "meeting_start_time": at("2024-05-06", "09:00"),
+                          "meeting_end_time": at("2024-05-06", "10:00"),
+                          "time_in_meeting": 60}],
+            "focus_time": [{"focus_time_start": at("2024-05-06", "10:00"),
+                            "focus_time_end": at("2024-05-06", "11:00"),
+                            "focus_time": 60}],
+            "breaks": [],
+            "projects": [],
+        }))
+        .unwrap();
+        assert_eq!(day.schema_version, CURRENT_SCHEMA_VERSION);
+        let meeting = &day.meetings[0];
+        assert!(meeting.billable);
+        assert!(meeting.notes.is_empty() && meeting.action_items.is_empty() && meeting.pauses.is_empty());
+        assert!(day.focus_time[0].billable && day.focus_time[0].pauses.is_empty());
+    }
+
+    #[test]
+    fn current_record_round_trips() {
+        let day = current_day();
+        let stored = serde_json::to_value(&day).unwrap();
+        assert_eq!(version(&stored), u64::from(CURRENT_SCHEMA_VERSION));
+        assert_eq!(upgrade(stored).unwrap(), day);
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        let mut stored = serde_json::to_value(current_day()).unwrap();
+        stored["schema_version"] = Value::from(CURRENT_SCHEMA_VERSION + 1);
+        assert!(upgrade(stored).is_err());
+    }
+
+    fn t(time: &str) -> Timestamp {
+        at("2024-05-06", time)
+    }
+
+    fn day(
+        meetings: Vec<MeetingList>,
+        focus_time: Vec<FocusTime>,
+        breaks: Vec<BreakTime>,
+        projects: Vec<ProjectTime>,
+    ) -> Worktime {
+        Worktime::new("2024-05-06".to_string(), t("08:00"), t("18:00"), meetings, focus_time, breaks, projects)
+    }
+
+    fn meeting(start: &str, end: &str, project: Option<&str>, billable: bool, pauses: Vec<Pause>) -> MeetingList {
+        MeetingList {
+            meeting_name: "m".to_string(),
+            meeting_start_time: t(start),
+            meeting_end_time: t(end),
+            time_in_meeting: 0,
+            project: project.map(String::from),
+            billable,
+            notes: String::new(),
+            action_items: Vec::new(),
+            pauses,
+        }
+    }
+
+    fn focus(start: &str, end: &str, project: Option<&str>) -> FocusTime {
+        FocusTime {
+            focus_time_start: t(start),
+            focus_time_end: t(end),
+            focus_time: 0,
+            project: project.map(String::from),
+            billable: true,
+            pauses: Vec::new(),
+        }
+    }
+
+    fn session(project: &str, task: Option<&str>, start: &str, end: &str) -> ProjectTime {
+        ProjectTime {
+            project: project.to_string(),
+            task: task.map(String::from),
+            project_start_time: t(start),
+            project_end_time: t(end),
+            time_in_project: 0,
+        }
+    }
+
+    fn pause(start: &str, end: &str) -> Pause {
+        Pause { start: t(start), end: t(end) }
+    }
+
+    fn key(project: &str, task: Option<&str>) -> (String, Option<String>) {
+        (project.to_string(), task.map(String::from))
+    }
+
+    #[test]
+    fn project_sessions_leave_out_breaks() {
+        let breaks = vec![BreakTime {
+            break_start_time: t("10:00"),
+            break_end_time: t("10:15"),
+            time_in_break: 15,
+        }];
+        let worktime = day(Vec::new(), Vec::new(), breaks, vec![session("A", Some("x"), "09:00", "11:00")]);
+        assert_eq!(worktime.task_minutes(false), BTreeMap::from([(key("A", Some("x")), 105)]));
+    }
+
+    #[test]
+    fn meeting_tagged_to_another_project_counts_once() {
+        let worktime = day(
+            vec![meeting("10:00", "10:30", Some("B"), true, Vec::new())],
+            Vec::new(),
+            Vec::new(),
+            vec![session("A", Some("x"), "09:00", "11:00")],
+        );
+        assert_eq!(
+            worktime.task_minutes(false),
+            BTreeMap::from([(key("A", Some("x")), 90), (key("B", None), 30)])
+        );
+        assert_eq!(worktime.project_minutes(), BTreeMap::from([("A".to_string(), 90), ("B".to_string(), 30)]));
+    }
+
+    #[test]
+    fn meeting_of_the_session_project_keeps_the_task() {
+        let worktime = day(
+            vec![meeting("10:00", "10:30", Some("A"), true, Vec::new())],
+            Vec::new(),
+            Vec::new(),
+            vec![session("A", Some("x"), "09:00", "11:00")],
+        );
+        assert_eq!(worktime.task_minutes(false), BTreeMap::from([(key("A", Some("x")), 120)]));
+    }
+
+    #[test]
+    fn untagged_meetings_count_for_the_session() {
+        let worktime = day(
+            vec![meeting("10:00", "10:30", None, true, Vec::new())],
+            Vec::new(),
+            Vec::new(),
+            vec![session("A", None, "09:00", "11:00")],
+        );
+        assert_eq!(worktime.task_minutes(false), BTreeMap::from([(key("A", None), 120)]));
+    }
+
+    #[test]
+    fn meetings_win_over_focus_sessions() {
+        // the meeting overlaps the end of one focus session and holds another
+        let worktime = day(
+            vec![meeting("10:00", "11:00", Some("B"), true, Vec::new())],
+            vec![focus("09:30", "10:30", Some("C")), focus("10:40", "10:50", Some("C"))],
+            Vec::new(),
+            Vec::new(),
+        );
+        assert_eq!(
+            worktime.task_minutes(false),
+            BTreeMap::from([(key("B", None), 60), (key("C", None), 30)])
+        );
+    }
+
+    #[test]
+    fn paused_meetings_give_the_time_back_to_the_session() {
+        let worktime = day(
+            vec![meeting("10:00", "11:00", Some("B"), true, vec![pause("10:20", "10:40")])],
+            Vec::new(),
+            Vec::new(),
+            vec![session("A", None, "09:00", "12:00")],
+        );
+        assert_eq!(
+            worktime.task_minutes(false),
+            BTreeMap::from([(key("A", None), 140), (key("B", None), 40)])
+        );
+    }
+
+    #[test]
+    fn billable_only_drops_non_billable_time() {
+        let worktime = day(
+            vec![meeting("10:00", "10:30", Some("B"), false, Vec::new())],
+            Vec::new(),
+            Vec::new(),
+            vec![session("A", None, "09:00", "11:00"), session("B", None, "14:00", "14:45")],
+        );
+        assert_eq!(
+            worktime.task_minutes(true),
+            BTreeMap::from([(key("A", None), 90), (key("B", None), 45)])
+        );
+        assert_eq!(
+            worktime.task_minutes(false),
+            BTreeMap::from([(key("A", None), 90), (key("B", None), 75)])
+        );
+    }
+
+    #[test]
+    fn sessions_without_time_left_are_listed() {
+        let worktime = day(
+            vec![meeting("09:00", "10:00", Some("B"), true, Vec::new())],
+            Vec::new(),
+            Vec::new(),
+            vec![session("A", Some("x"), "09:00", "10:00")],
+        );
+        assert_eq!(
+            worktime.task_minutes(false),
+            BTreeMap::from([(key("A", Some("x")), 0), (key("B", None), 60)])
+        );
+    }
+
+    #[test]
+    fn unpaused_spans_cut_at_pauses() {
+        assert_eq!(unpaused_spans(t("09:00"), t("10:00"), &[]), vec![(t("09:00"), t("10:00"))]);
+        // out of order, one at the start and one past the end
+        let pauses = [pause("09:50", "10:30"), pause("09:00", "09:10"), pause("09:20", "09:30")];
+        assert_eq!(
+            unpaused_spans(t("09:00"), t("10:00"), &pauses),
+            vec![(t("09:10"), t("09:20")), (t("09:30"), t("09:50"))]
+        );
+    }
+
+    #[test]
+    fn unpaused_spans_of_a_fully_paused_session_are_empty() {
+        assert!(unpaused_spans(t("09:00"), t("10:00"), &[pause("08:30", "10:30")]).is_empty());
+    }
+}
@@ -0,0 +1,396 @@
+use crate::config::Config;
+use crate::calc_time::format_minutes;
+use crate::model::Worktime;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_REPORT_DIR: &str = "data/reports";
+
+/// An ISO week or a calendar month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Week { year: i32, week: u32 },
+    Month { year: i32, month: u32 },
+}
+
+impl Period {
+    pub fn week_of(date: NaiveDate) -> Self {
+        let week = date.iso_week();
+        Period::Week {
+            year: week.year(),
+            week: week.week(),
+        }
+    }
+
+    pub fn month_of(date: NaiveDate) -> Self {
+        Period::Month {
+            year: date.year(),
+            month: date.month(),
+        }
+    }
+
+    pub fn first_day(&self) -> NaiveDate {
+        match *self {
+            Period::Week { year, week } => NaiveDate::from_isoywd_opt(year, week, Weekday::Mon),
+            Period::Month { year, month } => NaiveDate::from_ymd_opt(year, month, 1),
+        }
+        .expect("periods are built from valid dates")
+    }
+
+    pub fn last_day(&self) -> NaiveDate {
+        self.next().first_day() - Duration::days(1)
+    }
+
+    pub fn previous(&self) -> Self {
+        self.same_kind(self.first_day() - Duration::days(1))
+    }
+
+    pub fn next(&self) -> Self {
+        match *self {
+            Period::Week { .. } => self.same_kind(self.first_day() + Duration::days(7)),
+            Period::Month { year, month: 12 } => Period::Month { year: year + 1, month: 1 },
+            Period::Month { year, month } => Period::Month { year, month: month + 1 },
+        }
+    }
+
+    /// The week of a month's first day, or the month of a week's Monday.
+    pub fn toggle(&self) -> Self {
+        match self {
+            Period::Week { .. } => Period::month_of(self.first_day()),
+            Period::Month { .. } => Period::week_of(self.first_day()),
+        }
+    }
+
+    fn same_kind(&self, date: NaiveDate) -> Self {
+        match self {
+            Period::Week { .. } => Period::week_of(date),
+            Period::Month { .. } => Period::month_of(date),
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            Period::Week { year, week } => format!(
+                "Week {} {} ({} to {})",
+                week,
+                year,
+                self.first_day(),
+                self.last_day()
+            ),
+            Period::Month { .. } => self.first_day().format("%B %Y").to_string(),
+        }
+    }
+
+    /// Short name for file names, e.g. `2024-W07` or `2024-02`.
+    pub fn slug(&self) -> String {
+        match self {
+            Period::Week { year, week } => format!("{}-W{:02}", year, week),
+            Period::Month { year, month } => format!("{}-{:02}", year, month),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportDay {
+    pub date: NaiveDate,
+    /// Whether a record exists; without one everything but the target is zero.
+    pub recorded: bool,
+    pub worked: i64,
+    pub target: i64,
+    pub meetings: i64,
+    pub focus: i64,
+    pub breaks: i64,
+    pub projects: BTreeMap<String, i64>,
+}
+
+impl ReportDay {
+    pub fn overtime(&self) -> i64 {
+        self.worked - self.target
+    }
+}
+
+/// Time booked to one project within a period.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectTotal {
+    pub name: String,
+    pub cost_center: Option<String>,
+    pub minutes: i64,
+}
+
+/// Aggregates of one period. Like the balance, days count up to yesterday;
+/// today only once it is stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub period: Period,
+    pub days: Vec<ReportDay>,
+    /// Largest first.
+    pub projects: Vec<ProjectTotal>,
+}
+
+impl Report {
+    pub fn build(worktime: &[Worktime], config: &Config, period: Period, today: NaiveDate) -> Self {
+        let mut recorded: HashMap<NaiveDate, ReportDay> = HashMap::new();
+        for day in worktime {
+            let Ok(date) = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d") else {
+                continue;
+            };
+            let entry = recorded.entry(date).or_insert_with(|| ReportDay {
+                date,
+                recorded: true,
+                worked: 0,
+                target: config.schedule.target_minutes(date),
+                meetings: 0,
+                focus: 0,
+                breaks: 0,
+                projects: BTreeMap::new(),
+            });
+            for (project, minutes) in day.project_minutes() {
+                *entry.projects.entry(project).or_default() += minutes;
+            }
+            entry.worked += day.worked_minutes(&config.breaks);
+            entry.meetings += day.meetings.iter().map(|m| i64::from(m.time_in_meeting)).sum::<i64>();
+            entry.focus += day.focus_time.iter().map(|f| i64::from(f.focus_time)).sum::<i64>();
+            entry.breaks += day.break_minutes();
+        }
+
+        let days = period
+            .first_day()
+            .iter_days()
+            .take_while(|date| *date <= period.last_day())
+            .filter_map(|date| match recorded.remove(&date) {
+                Some(day) => Some(day),
+                None if date < today => Some(ReportDay {
+                    date,
+                    recorded: false,
+                    worked: 0,
+                    target: config.schedule.target_minutes(date),
+                    meetings: 0,
+                    focus: 0,
+                    breaks: 0,
+                    projects: BTreeMap::new(),
+                }),
+                None => None,
+            })
+            .collect::<Vec<ReportDay>>();
+
+        let mut totals: BTreeMap<&str, i64> = BTreeMap::new();
+        for (project, minutes) in days.iter().flat_map(|day| &day.projects) {
+            *totals.entry(project).or_default() += minutes;
+        }
+        let mut projects: Vec<ProjectTotal> = totals
+            .into_iter()
+            .map(|(name, minutes)| ProjectTotal {
+                name: name.to_string(),
+                cost_center: config.project(name).and_then(|p| p.cost_center.clone()),
+                minutes,
+            })
+            .collect();
+        projects.sort_by_key(|p| std::cmp::Reverse(p.minutes));
+        Self { period, days, projects }
+    }
+
+    fn worked_days(&self) -> impl Iterator<Item = &ReportDay> {
+        self.days.iter().filter(|day| day.recorded)
+    }
+
+    pub fn total_worked(&self) -> i64 {
+        self.days.iter().map(|day| day.worked).sum()
+    }
+
+    pub fn total_target(&self) -> i64 {
+        self.days.iter().map(|day| day.target).sum()
+    }
+
+    pub fn total_meetings(&self) -> i64 {
+        self.days.iter().map(|day| day.meetings).sum()
+    }
+
+    pub fn total_focus(&self) -> i64 {
+        self.days.iter().map(|day| day.focus).sum()
+    }
+
+    pub fn overtime(&self) -> i64 {
+        self.total_worked() - self.total_target()
+    }
+
+    /// Average over the days with a record.
+    pub fn average_worked(&self) -> Option<i64> {
+        let count = self.worked_days().count() as i64;
+        (count > 0).then(|| self.total_worked() / count)
+    }
+
+    /// Meeting time in percent of the worked time.
+    pub fn meeting_share(&self) -> Option<f64> {
+        share(self.total_meetings(), self.total_worked())
+    }
+
+    /// Focus time in percent of the worked time.
+    pub fn focus_share(&self) -> Option<f64> {
+        share(self.total_focus(), self.total_worked())
+    }
+
+    pub fn longest(&self) -> Option<&ReportDay> {
+        self.worked_days().max_by_key(|day| day.worked)
+    }
+
+    pub fn shortest(&self) -> Option<&ReportDay> {
+        self.worked_days().min_by_key(|day| day.worked)
+    }
+
+    /// The summary as (label, value) pairs, shared by all output formats.
+    pub fn summary(&self) -> Vec<(&'static str, String)> {
+        let day = |day: Option<&ReportDay>| {
+            day.map(|d| format!("{} ({})", format_minutes(d.worked), d.date.format("%a %Y-%m-%d")))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        let percent = |share: Option<f64>| share.map(|s| format!("{:.0} %", s)).unwrap_or_else(|| "-".to_string());
+        vec![
+            ("Days worked", self.worked_days().count().to_string()),
+            ("Total worktime", format_minutes(self.total_worked())),
+            (
+                "Average worktime",
+                self.average_worked().map(format_minutes).unwrap_or_else(|| "-".to_string()),
+            ),
+            ("Target", format_minutes(self.total_target())),
+            ("Overtime", format_minutes(self.overtime())),
+            ("Meeting share", percent(self.meeting_share())),
+            ("Focus share", percent(self.focus_share())),
+            ("Longest day", day(self.longest())),
+            ("Shortest day", day(self.shortest())),
+        ]
+    }
+
+    /// (project, cost center, time, share of the worked time) per project.
+    pub fn project_rows(&self) -> Vec<[String; 4]> {
+        self.projects
+            .iter()
+            .map(|project| {
+                [
+                    project.name.clone(),
+                    project.cost_center.clone().unwrap_or_default(),
+                    format_minutes(project.minutes),
+                    share(project.minutes, self.total_worked())
+                        .map(|s| format!("{:.0} %", s))
+                        .unwrap_or_else(|| "-".to_string()),
+                ]
+            })
+            .collect()
+    }
+
+    fn day_columns(day: &ReportDay) -> [String; 7] {
+        [
+            day.date.format("%a %Y-%m-%d").to_string(),
+            format_minutes(day.worked),
+            format_minutes(day.target),
+            format_minutes(day.overtime()),
+            format_minutes(day.meetings),
+            format_minutes(day.focus),
+            format_minutes(day.breaks),
+        ]
+    }
+
+    const DAY_HEADER: [&'static str; 7] = ["Day", "Worked", "Target", "Overtime", "Meetings", "Focus", "Breaks"];
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n", self.period.title());
+        let _ = writeln!(out, "| | |\n|---|---:|");
+        for (label, value) in self.summary() {
+            let _ = writeln!(out, "| {} | {} |", label, value);
+        }
+        if !self.projects.is_empty() {
+            let _ = writeln!(out, "\n## Projects\n");
+            let _ = writeln!(out, "| Project | Cost center | Time | Share |\n|---|---|---:|---:|");
+            for row in self.project_rows() {
+                let row = row.map(|c| escape_markdown_cell(&c));
+                let _ = writeln!(out, "| {} |", row.join(" | "));
+            }
+        }
+        let _ = writeln!(out, "\n## Days\n");
+        let _ = writeln!(out, "| {} |", Self::DAY_HEADER.join(" | "));
+        let _ = writeln!(out, "|---|---:|---:|---:|---:|---:|---:|");
+        for day in &self.days {
+            let _ = writeln!(out, "| {} |", Self::day_columns(day).join(" | "));
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        let title = self.period.title();
+        let _ = writeln!(out, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">");
+        let _ = writeln!(out, "<title>{}</title>", escape_html(&title));
+        let _ = writeln!(
+            out,
+            "<style>body {{ font-family: sans-serif; }} table {{ border-collapse: collapse; margin-bottom: 1em; }} \
+             td, th {{ border: 1px solid #ccc; padding: 2px 8px; }} td + td {{ text-align: right; }}</style>"
+        );
+        let _ = writeln!(out, "</head>\n<body>\n<h1>{}</h1>\n<table>", escape_html(&title));
+        for (label, value) in self.summary() {
+            let _ = writeln!(out, "<tr><th>{}</th><td>{}</td></tr>", escape_html(label), escape_html(&value));
+        }
+        let _ = writeln!(out, "</table>");
+        if !self.projects.is_empty() {
+            let _ = writeln!(out, "<h2>Projects</h2>\n<table>");
+            let _ = writeln!(out, "<tr><th>Project</th><th>Cost center</th><th>Time</th><th>Share</th></tr>");
+            for row in self.project_rows() {
+                let row = row.map(|c| escape_html(&c));
+                let _ = writeln!(out, "<tr><td>{}</td></tr>", row.join("</td><td>"));
+            }
+            let _ = writeln!(out, "</table>");
+        }
+        let _ = writeln!(out, "<h2>Days</h2>\n<table>");
+        let _ = writeln!(out, "<tr><th>{}</th></tr>", Self::DAY_HEADER.join("</th><th>"));
+        for day in &self.days {
+            let columns = Self::day_columns(day).map(|c| escape_html(&c));
+            let _ = writeln!(out, "<tr><td>{}</td></tr>", columns.join("</td><td>"));
+        }
+        let _ = writeln!(out, "</table>\n</body>\n</html>");
+        out
+    }
+}
+
+impl Report {
+    /// Write the report as `<period>.md` and `<period>.html` into `dir`.
+    pub fn write_files(&self, dir: impl AsRef<Path>) -> anyhow::Result<Vec<PathBuf>> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let markdown = dir.join(format!("{}.md", self.period.slug()));
+        let html = dir.join(format!("{}.html", self.period.slug()));
+        std::fs::write(&markdown, self.to_markdown())?;
+        std::fs::write(&html, self.to_html())?;
+        Ok(vec![markdown, html])
+    }
+}
+
+/// `text` as HTML element content; names come from the user.
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// `text` as the content of a Markdown table cell: a `|` would end the cell
+/// and a line break the row.
+pub fn escape_markdown_cell(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", " ")
+        .replace(['\r', '\n'], " ")
+}
+
+fn share(part: i64, total: i64) -> Option<f64> {
+    (total > 0).then(|| part as f64 * 100.0 / total as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_cells_keep_pipes_and_line_breaks_in() {
+        assert_eq!(escape_markdown_cell("A|B"), "A\\|B");
+        assert_eq!(escape_markdown_cell("one\ntwo\r\nthree"), "one two three");
+        assert_eq!(escape_markdown_cell("C:\\temp|x"), "C:\\\\temp\\|x");
+    }
+}
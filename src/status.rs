@@ -0,0 +1,158 @@
+use crate::app::App;
+use crate::calc_time::{format_minutes, minutes_between};
+use crate::model::{billable_default, is_billable, Timestamp};
+use serde::{Deserialize, Serialize};
+
+/// A snapshot of the running day for shell prompts and status bars.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Status {
+    /// False when no start was recorded yet and the default start is assumed.
+    pub started: bool,
+    pub starttime: Timestamp,
+    pub worked_minutes: i64,
+    pub target_minutes: i64,
+    pub predicted_endtime: Timestamp,
+    /// The overtime balance including today.
+    #[serde(default)]
+    pub balance: i64,
+    pub balance_today: i64,
+    pub meeting: Option<RunningMeeting>,
+    pub focus: Option<RunningSession>,
+    #[serde(rename = "break")]
+    pub on_break: Option<RunningSession>,
+    /// Minutes until the break rules require a break, negative when overdue.
+    pub break_due_in: Option<i64>,
+    #[serde(default)]
+    pub project: Option<RunningProject>,
+    /// The date of an earlier day left in the journal and not saved yet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unsaved_day: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunningMeeting {
+    pub name: String,
+    pub since: Timestamp,
+    /// Without the pauses.
+    pub minutes: i64,
+    #[serde(default = "billable_default", skip_serializing_if = "is_billable")]
+    pub billable: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub paused: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunningSession {
+    pub since: Timestamp,
+    /// Without the pauses of a focus session.
+    pub minutes: i64,
+    /// Always set for breaks.
+    #[serde(default = "billable_default", skip_serializing_if = "is_billable")]
+    pub billable: bool,
+    /// Never set for breaks.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub paused: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunningProject {
+    pub name: String,
+    pub task: Option<String>,
+    pub since: Timestamp,
+    pub minutes: i64,
+}
+
+/// Placeholders understood by [`Status::format`].
+pub const PLACEHOLDERS: [&str; 15] = [
+    "{start}",
+    "{worked}",
+    "{worked_minutes}",
+    "{target}",
+    "{left}",
+    "{end}",
+    "{balance}",
+    "{today}",
+    "{meeting}",
+    "{meeting_minutes}",
+    "{focus}",
+    "{focus_minutes}",
+    "{break}",
+    "{project}",
+    "{project_minutes}",
+];
+
+impl Status {
+    pub fn of(app: &App, at: Timestamp) -> Self {
+        Self {
+            started: app.starttime_pairs.contains_key(&app.starttime_key),
+            starttime: app.starttime(),
+            worked_minutes: app.worked_minutes(at).max(0),
+            target_minutes: app.target_minutes(),
+            predicted_endtime: app.predicted_endtime(),
+            balance: app.balance_total(at),
+            balance_today: app.balance_today(at),
+            meeting: app.meeting_running.then(|| RunningMeeting {
+                name: app.meeting_name.clone(),
+                since: app.meeting_start_time,
+                minutes: app.meeting_minutes(at),
+                billable: app.meeting_billable,
+                paused: app.meeting_paused,
+            }),
+            focus: app.focus.then(|| RunningSession {
+                since: app.focus_time_start,
+                minutes: app.focus_minutes(at),
+                billable: app.focus_billable,
+                paused: app.focus_paused,
+            }),
+            on_break: app.break_running.then(|| RunningSession {
+                since: app.break_start_time,
+                minutes: minutes_between(app.break_start_time, at),
+                billable: true,
+                paused: false,
+            }),
+            break_due_in: app.break_due_in(),
+            project: app.project_running.then(|| RunningProject {
+                name: app.project_name.clone(),
+                task: app.project_task.clone(),
+                since: app.project_start_time,
+                minutes: minutes_between(app.project_start_time, at),
+            }),
+            unsaved_day: None,
+        }
+    }
+
+    /// Fill the [`PLACEHOLDERS`] in `template`. Placeholders of sessions that
+    /// are not running become empty.
+    pub fn format(&self, template: &str) -> String {
+        let minutes = |m: Option<i64>| m.map(|m| m.to_string()).unwrap_or_default();
+        let values = [
+            self.starttime.format("%H:%M").to_string(),
+            format_minutes(self.worked_minutes),
+            self.worked_minutes.to_string(),
+            format_minutes(self.target_minutes),
+            format_minutes(self.target_minutes - self.worked_minutes),
+            self.predicted_endtime.format("%H:%M").to_string(),
+            format_minutes(self.balance),
+            format_minutes(self.balance_today),
+            self.meeting.as_ref().map(|m| m.name.clone()).unwrap_or_default(),
+            minutes(self.meeting.as_ref().map(|m| m.minutes)),
+            if self.focus.is_some() { "focus" } else { "" }.to_string(),
+            minutes(self.focus.as_ref().map(|f| f.minutes)),
+            minutes(self.on_break.as_ref().map(|b| b.minutes)),
+            self.project
+                .as_ref()
+                .map(|p| match &p.task {
+                    Some(task) => format!("{}/{}", p.name, task),
+                    None => p.name.clone(),
+                })
+                .unwrap_or_default(),
+            minutes(self.project.as_ref().map(|p| p.minutes)),
+        ];
+        PLACEHOLDERS
+            .iter()
+            .zip(values)
+            .fold(template.to_string(), |text, (placeholder, value)| {
+                text.replace(placeholder, &value)
+            })
+    }
+}
@@ -0,0 +1,52 @@
+mod jsonl;
+mod sqlite;
+
+pub use jsonl::JsonlStore;
+pub use sqlite::SqliteStore;
+
+use crate::model::Worktime;
+use anyhow::Result;
+
+pub const DEFAULT_JSONL_PATH: &str = "data/worktime.jsonl";
+pub const DEFAULT_SQLITE_PATH: &str = "data/worktime.db";
+
+/// Persistence for finished work days. Every widget and the exporter go
+/// through this trait, so a new backend only needs another implementation.
+pub trait WorktimeStore {
+    /// All stored days, in the order the backend keeps them.
+    fn load_all(&self) -> Result<Vec<Worktime>>;
+
+    /// Days with `from <= date <= to`. Dates are `%Y-%m-%d` strings, so a
+    /// plain string comparison orders them correctly.
+    fn load_range(&self, from: &str, to: &str) -> Result<Vec<Worktime>> {
+        Ok(self
+            .load_all()?
+            .into_iter()
+            .filter(|day| day.date.as_str() >= from && day.date.as_str() <= to)
+            .collect())
+    }
+
+    /// Insert the day, replacing any stored record with the same date.
+    fn upsert(&self, worktime: &Worktime) -> Result<()>;
+
+    /// Remove the day with the given date. Returns whether a day was removed.
+    fn delete(&self, date: &str) -> Result<bool>;
+}
+
+/// The store used by the TUI: the SQLite database once it has been created
+/// by `migrate`, the JSON Lines file otherwise.
+pub fn open_default() -> Result<Box<dyn WorktimeStore>> {
+    if std::path::Path::new(DEFAULT_SQLITE_PATH).exists() {
+        Ok(Box::new(SqliteStore::open(DEFAULT_SQLITE_PATH)?))
+    } else {
+        Ok(Box::new(JsonlStore::new(DEFAULT_JSONL_PATH)))
+    }
+}
+
+/// One-shot import of the JSON Lines history into the SQLite database.
+/// Returns the number of imported days.
+pub fn migrate_jsonl_to_sqlite(jsonl_path: &str, sqlite_path: &str) -> Result<usize> {
+    let source = JsonlStore::new(jsonl_path);
+    let target = SqliteStore::open(sqlite_path)?;
+    target.import_from(&source)
+}
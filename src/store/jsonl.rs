@@ -0,0 +1,76 @@
+use crate::model::{merge_duplicate_days, upgrade, Worktime};
+use crate::store::WorktimeStore;
+use anyhow::Result;
+use serde_jsonlines::{json_lines, WriteExt};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// One `Worktime` record per line in a JSON Lines file.
+pub struct JsonlStore {
+    path: PathBuf,
+}
+
+impl JsonlStore {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Rewrite the whole file. The days go to a sibling temp file first which
+    /// then replaces the original, so a crash never leaves a half-written file.
+    fn write_all(&self, days: &[Worktime]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        writer.write_json_lines(days)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        drop(writer);
+
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+impl WorktimeStore for JsonlStore {
+    fn load_all(&self) -> Result<Vec<Worktime>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let worktime_days = json_lines::<serde_json::Value, _>(&self.path)?
+            .map(|line| upgrade(line?))
+            .collect::<Result<Vec<Worktime>>>()?;
+        Ok(merge_duplicate_days(worktime_days))
+    }
+
+    fn upsert(&self, worktime: &Worktime) -> Result<()> {
+        let mut days = self.load_all()?;
+        match days.iter_mut().find(|day| day.date == worktime.date) {
+            Some(day) => *day = worktime.clone(),
+            None => days.push(worktime.clone()),
+        }
+        self.write_all(&days)
+    }
+
+    fn delete(&self, date: &str) -> Result<bool> {
+        let mut days = self.load_all()?;
+        let before = days.len();
+        days.retain(|day| day.date != date);
+        if days.len() == before {
+            return Ok(false);
+        }
+        self.write_all(&days)?;
+        Ok(true)
+    }
+}
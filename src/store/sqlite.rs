@@ -0,0 +1,456 @@
+use crate::calc_time::legacy_timestamp;
+use crate::model::{merge_duplicate_days, ActionItem, BreakTime, FocusTime, MeetingList, Pause, ProjectTime, Worktime, CURRENT_SCHEMA_VERSION};
+use crate::store::WorktimeStore;
+use anyhow::Result;
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::path::Path;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS days (
+        date      TEXT PRIMARY KEY,
+        starttime TEXT NOT NULL,
+        endtime   TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS meetings (
+        id                 INTEGER PRIMARY KEY,
+        date               TEXT NOT NULL REFERENCES days(date) ON DELETE CASCADE,
+        position           INTEGER NOT NULL,
+        meeting_name       TEXT NOT NULL,
+        meeting_start_time TEXT NOT NULL,
+        meeting_end_time   TEXT NOT NULL,
+        time_in_meeting    INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS meetings_date ON meetings(date);
+    CREATE TABLE IF NOT EXISTS focus_sessions (
+        id               INTEGER PRIMARY KEY,
+        date             TEXT NOT NULL REFERENCES days(date) ON DELETE CASCADE,
+        position         INTEGER NOT NULL,
+        focus_time_start TEXT NOT NULL,
+        focus_time_end   TEXT NOT NULL,
+        focus_time       INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS focus_sessions_date ON focus_sessions(date);
+";
+
+const BREAKS_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS breaks (
+        id               INTEGER PRIMARY KEY,
+        date             TEXT NOT NULL REFERENCES days(date) ON DELETE CASCADE,
+        position         INTEGER NOT NULL,
+        break_start_time TEXT NOT NULL,
+        break_end_time   TEXT NOT NULL,
+        time_in_break    INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS breaks_date ON breaks(date);
+";
+
+const PROJECTS_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS project_sessions (
+        id                 INTEGER PRIMARY KEY,
+        date               TEXT NOT NULL REFERENCES days(date) ON DELETE CASCADE,
+        position           INTEGER NOT NULL,
+        project            TEXT NOT NULL,
+        task               TEXT,
+        project_start_time TEXT NOT NULL,
+        project_end_time   TEXT NOT NULL,
+        time_in_project    INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS project_sessions_date ON project_sessions(date);
+    ALTER TABLE meetings ADD COLUMN project TEXT;
+    ALTER TABLE focus_sessions ADD COLUMN project TEXT;
+";
+
+const BILLABLE_SCHEMA: &str = "
+    ALTER TABLE meetings ADD COLUMN billable INTEGER NOT NULL DEFAULT 1;
+    ALTER TABLE focus_sessions ADD COLUMN billable INTEGER NOT NULL DEFAULT 1;
+";
+
+const NOTES_SCHEMA: &str = "
+    ALTER TABLE meetings ADD COLUMN notes TEXT NOT NULL DEFAULT '';
+";
+
+// Items belong to the meeting at `meeting` in the day's list, like the
+// meetings belong to the day by position.
+const ACTION_ITEMS_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS action_items (
+        id       INTEGER PRIMARY KEY,
+        date     TEXT NOT NULL REFERENCES days(date) ON DELETE CASCADE,
+        meeting  INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        text     TEXT NOT NULL,
+        owner    TEXT,
+        due      TEXT,
+        done     INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE INDEX IF NOT EXISTS action_items_date ON action_items(date);
+";
+
+// Pauses of the meeting or focus session (`session`) at `position` in the
+// day's list.
+const PAUSES_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS pauses (
+        id               INTEGER PRIMARY KEY,
+        date             TEXT NOT NULL REFERENCES days(date) ON DELETE CASCADE,
+        session          TEXT NOT NULL,
+        position         INTEGER NOT NULL,
+        pause_start_time TEXT NOT NULL,
+        pause_end_time   TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS pauses_date ON pauses(date);
+";
+
+/// Days, meetings, focus sessions, breaks and project sessions in an
+/// embedded SQLite database.
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        migrate(&conn)?;
+        Ok(Self { conn })
+    }
+
+    /// Import every day of another store, replacing days that already exist.
+    /// Records sharing a date, as the append-only export left them, become one
+    /// day. Returns the number of imported days.
+    pub fn import_from(&self, other: &dyn WorktimeStore) -> Result<usize> {
+        let days = merge_duplicate_days(other.load_all()?);
+        let tx = self.conn.unchecked_transaction()?;
+        for day in &days {
+            write_day(&tx, day)?;
+        }
+        tx.commit()?;
+        Ok(days.len())
+    }
+
+    fn query_days(&self, sql: &str, params: &[&dyn rusqlite::ToSql]) -> Result<Vec<Worktime>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let mut days = stmt
+            .query_map(params, |row| {
+                Ok(Worktime {
+                    schema_version: CURRENT_SCHEMA_VERSION,
+                    date: row.get(0)?,
+                    starttime: row.get(1)?,
+                    endtime: row.get(2)?,
+                    meetings: Vec::new(),
+                    focus_time: Vec::new(),
+                    breaks: Vec::new(),
+                    projects: Vec::new(),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut meetings = self.conn.prepare(
+            "SELECT meeting_name, meeting_start_time, meeting_end_time, time_in_meeting, project, billable, notes
+             FROM meetings WHERE date = ?1 ORDER BY position",
+        )?;
+        let mut action_items = self.conn.prepare(
+            "SELECT meeting, text, owner, due, done
+             FROM action_items WHERE date = ?1 ORDER BY meeting, position",
+        )?;
+        let mut pauses = self.conn.prepare(
+            "SELECT session, position, pause_start_time, pause_end_time
+             FROM pauses WHERE date = ?1 ORDER BY pause_start_time",
+        )?;
+        let mut focus = self.conn.prepare(
+            "SELECT focus_time_start, focus_time_end, focus_time, project, billable
+             FROM focus_sessions WHERE date = ?1 ORDER BY position",
+        )?;
+        let mut breaks = self.conn.prepare(
+            "SELECT break_start_time, break_end_time, time_in_break
+             FROM breaks WHERE date = ?1 ORDER BY position",
+        )?;
+        let mut projects = self.conn.prepare(
+            "SELECT project, task, project_start_time, project_end_time, time_in_project
+             FROM project_sessions WHERE date = ?1 ORDER BY position",
+        )?;
+        for day in days.iter_mut() {
+            day.meetings = meetings
+                .query_map([&day.date], |row| {
+                    Ok(MeetingList {
+                        meeting_name: row.get(0)?,
+                        meeting_start_time: row.get(1)?,
+                        meeting_end_time: row.get(2)?,
+                        time_in_meeting: row.get(3)?,
+                        project: row.get(4)?,
+                        billable: row.get(5)?,
+                        notes: row.get(6)?,
+                        action_items: Vec::new(),
+                        pauses: Vec::new(),
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            let items = action_items
+                .query_map([&day.date], |row| {
+                    let meeting: usize = row.get(0)?;
+                    let item = ActionItem {
+                        text: row.get(1)?,
+                        owner: row.get(2)?,
+                        due: row.get(3)?,
+                        done: row.get(4)?,
+                    };
+                    Ok((meeting, item))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            for (meeting, item) in items {
+                if let Some(meeting) = day.meetings.get_mut(meeting) {
+                    meeting.action_items.push(item);
+                }
+            }
+            day.focus_time = focus
+                .query_map([&day.date], |row| {
+                    Ok(FocusTime {
+                        focus_time_start: row.get(0)?,
+                        focus_time_end: row.get(1)?,
+                        focus_time: row.get(2)?,
+                        project: row.get(3)?,
+                        billable: row.get(4)?,
+                        pauses: Vec::new(),
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            let day_pauses = pauses
+                .query_map([&day.date], |row| {
+                    let session: String = row.get(0)?;
+                    let position: usize = row.get(1)?;
+                    Ok((session, position, Pause { start: row.get(2)?, end: row.get(3)? }))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            for (session, position, pause) in day_pauses {
+                let pauses = match session.as_str() {
+                    "meeting" => day.meetings.get_mut(position).map(|m| &mut m.pauses),
+                    _ => day.focus_time.get_mut(position).map(|f| &mut f.pauses),
+                };
+                if let Some(pauses) = pauses {
+                    pauses.push(pause);
+                }
+            }
+            day.breaks = breaks
+                .query_map([&day.date], |row| {
+                    Ok(BreakTime {
+                        break_start_time: row.get(0)?,
+                        break_end_time: row.get(1)?,
+                        time_in_break: row.get(2)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            day.projects = projects
+                .query_map([&day.date], |row| {
+                    Ok(ProjectTime {
+                        project: row.get(0)?,
+                        task: row.get(1)?,
+                        project_start_time: row.get(2)?,
+                        project_end_time: row.get(3)?,
+                        time_in_project: row.get(4)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+        }
+        Ok(days)
+    }
+}
+
+/// Create or upgrade the tables. The database tracks its own layout in
+/// `PRAGMA user_version`, independent of the JSON record version.
+fn migrate(conn: &Connection) -> Result<()> {
+    let version: i32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version < 1 {
+        conn.execute_batch(SCHEMA)?;
+        conn.pragma_update(None, "user_version", 1)?;
+    }
+    if version < 2 {
+        upgrade_legacy_times(conn)?;
+        conn.pragma_update(None, "user_version", 2)?;
+    }
+    if version < 3 {
+        conn.execute_batch(BREAKS_SCHEMA)?;
+        conn.pragma_update(None, "user_version", 3)?;
+    }
+    if version < 4 {
+        conn.execute_batch(PROJECTS_SCHEMA)?;
+        conn.pragma_update(None, "user_version", 4)?;
+    }
+    if version < 5 {
+        conn.execute_batch(BILLABLE_SCHEMA)?;
+        conn.pragma_update(None, "user_version", 5)?;
+    }
+    if version < 6 {
+        conn.execute_batch(NOTES_SCHEMA)?;
+        conn.pragma_update(None, "user_version", 6)?;
+    }
+    if version < 7 {
+        conn.execute_batch(ACTION_ITEMS_SCHEMA)?;
+        conn.pragma_update(None, "user_version", 7)?;
+    }
+    if version < 8 {
+        conn.execute_batch(PAUSES_SCHEMA)?;
+        conn.pragma_update(None, "user_version", 8)?;
+    }
+    Ok(())
+}
+
+// Version 1 stored "HH:MM" strings; turn them into timestamps on the day's
+// date. Times before the day's start went past midnight.
+fn upgrade_legacy_times(conn: &Connection) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    let mut day_starts = HashMap::new();
+    for (table, column) in [
+        ("days", "starttime"),
+        ("days", "endtime"),
+        ("meetings", "meeting_start_time"),
+        ("meetings", "meeting_end_time"),
+        ("focus_sessions", "focus_time_start"),
+        ("focus_sessions", "focus_time_end"),
+    ] {
+        let mut stmt = tx.prepare(&format!("SELECT rowid, date, {column} FROM {table}"))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for (rowid, date, value) in rows {
+            let day_start = day_starts.get(&date).copied();
+            if let Some(timestamp) = legacy_timestamp(&date, &value, day_start) {
+                tx.execute(
+                    &format!("UPDATE {table} SET {column} = ?1 WHERE rowid = ?2"),
+                    params![timestamp, rowid],
+                )?;
+                if column == "starttime" {
+                    day_starts.insert(date, timestamp);
+                }
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+fn write_day(conn: &Connection, day: &Worktime) -> Result<()> {
+    conn.execute("DELETE FROM days WHERE date = ?1", [&day.date])?;
+    conn.execute(
+        "INSERT INTO days (date, starttime, endtime) VALUES (?1, ?2, ?3)",
+        params![day.date, day.starttime, day.endtime],
+    )?;
+    for (position, meeting) in day.meetings.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO meetings
+             (date, position, meeting_name, meeting_start_time, meeting_end_time, time_in_meeting, project, billable, notes)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                day.date,
+                position,
+                meeting.meeting_name,
+                meeting.meeting_start_time,
+                meeting.meeting_end_time,
+                meeting.time_in_meeting,
+                meeting.project,
+                meeting.billable,
+                meeting.notes
+            ],
+        )?;
+        for (item_position, item) in meeting.action_items.iter().enumerate() {
+            conn.execute(
+                "INSERT INTO action_items (date, meeting, position, text, owner, due, done)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![day.date, position, item_position, item.text, item.owner, item.due, item.done],
+            )?;
+        }
+    }
+    for (position, focus) in day.focus_time.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO focus_sessions
+             (date, position, focus_time_start, focus_time_end, focus_time, project, billable)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                day.date,
+                position,
+                focus.focus_time_start,
+                focus.focus_time_end,
+                focus.focus_time,
+                focus.project,
+                focus.billable
+            ],
+        )?;
+    }
+    let session_pauses = day
+        .meetings
+        .iter()
+        .enumerate()
+        .flat_map(|(position, m)| m.pauses.iter().map(move |pause| ("meeting", position, pause)))
+        .chain(
+            day.focus_time
+                .iter()
+                .enumerate()
+                .flat_map(|(position, f)| f.pauses.iter().map(move |pause| ("focus", position, pause))),
+        );
+    for (session, position, pause) in session_pauses {
+        conn.execute(
+            "INSERT INTO pauses (date, session, position, pause_start_time, pause_end_time)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![day.date, session, position, pause.start, pause.end],
+        )?;
+    }
+    for (position, pause) in day.breaks.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO breaks
+             (date, position, break_start_time, break_end_time, time_in_break)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                day.date,
+                position,
+                pause.break_start_time,
+                pause.break_end_time,
+                pause.time_in_break
+            ],
+        )?;
+    }
+    for (position, session) in day.projects.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO project_sessions
+             (date, position, project, task, project_start_time, project_end_time, time_in_project)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                day.date,
+                position,
+                session.project,
+                session.task,
+                session.project_start_time,
+                session.project_end_time,
+                session.time_in_project
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+impl WorktimeStore for SqliteStore {
+    fn load_all(&self) -> Result<Vec<Worktime>> {
+        self.query_days("SELECT date, starttime, endtime FROM days ORDER BY date", &[])
+    }
+
+    fn load_range(&self, from: &str, to: &str) -> Result<Vec<Worktime>> {
+        self.query_days(
+            "SELECT date, starttime, endtime FROM days WHERE date BETWEEN ?1 AND ?2 ORDER BY date",
+            &[&from, &to],
+        )
+    }
+
+    fn upsert(&self, worktime: &Worktime) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        write_day(&tx, worktime)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn delete(&self, date: &str) -> Result<bool> {
+        let removed = self.conn.execute("DELETE FROM days WHERE date = ?1", [date])?;
+        Ok(removed > 0)
+    }
+}
@@ -1,9 +1,13 @@
+mod balance;
 mod barchart_tab;
 mod edit_history;
 mod meeting_notes;
 mod focus_time;
+mod report;
 
+pub use balance::BalanceTab;
 pub use barchart_tab::BarChartTab;
 pub use edit_history::EditHistoryTab;
-pub use meeting_notes::MeetingNotesTab;
-pub use focus_time::FocusTimeTab;
\ No newline at end of file
+pub use meeting_notes::{ActionItemEntry, MeetingNotesTab, NoteEditor, NoteEntry, NoteTarget, NotesView};
+pub use focus_time::FocusTimeTab;
+pub use report::ReportTab;
\ No newline at end of file
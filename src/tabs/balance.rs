@@ -0,0 +1,123 @@
+use ratatui::{
+    buffer::Buffer, 
+    layout::{
+        Constraint, 
+        Layout, 
+        Rect
+    }, 
+    style::{
+        palette::tailwind, Color, Style, 
+    }, 
+    symbols::border::PROPORTIONAL_TALL, 
+    text::Line,
+    widgets::{
+        Block, List, ListItem, Padding, Paragraph, Widget
+    }
+};
+use chrono::Local;
+use itertools::Itertools;
+use crate::balance::Balance;
+use crate::calc_time::format_minutes;
+use crate::config::Config;
+use crate::model::{merge_duplicate_days, Worktime};
+use crate::read_json::read_json;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceTab {
+    balance: Balance,
+    /// Today's record if it was stored already, e.g. before a restart.
+    stored_today: Option<Worktime>,
+}
+
+impl BalanceTab {
+    pub fn new() -> Self {
+        Self::with_config(&Config::load_default().unwrap_or_default())
+    }
+
+    /// A tab without data, for an app that never renders it.
+    pub fn empty() -> Self {
+        Self {
+            balance: Balance::default(),
+            stored_today: None,
+        }
+    }
+
+    /// The account up to yesterday; today is still running and shown live.
+    pub fn with_config(config: &Config) -> Self {
+        let worktime = read_json().unwrap_or_default();
+        let today = Local::now().date_naive();
+        let date = today.format("%Y-%m-%d").to_string();
+        Self {
+            balance: Balance::calculate(&worktime, config, today),
+            stored_today: merge_duplicate_days(worktime.into_iter().filter(|d| d.date == date).collect()).pop(),
+        }
+    }
+
+    pub fn balance(&self) -> &Balance {
+        &self.balance
+    }
+
+    pub fn stored_today(&self) -> Option<&Worktime> {
+        self.stored_today.as_ref()
+    }
+
+    fn block(&self) -> Block<'static> {
+        Block::bordered()
+            .border_set(PROPORTIONAL_TALL)
+            .padding(Padding::horizontal(1))
+            .border_style(tailwind::AMBER.c700)
+    }
+}
+
+impl Default for BalanceTab {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for &BalanceTab {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = self.block();
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let vertical = Layout::vertical([Constraint::Length(4), Constraint::Min(0)]);
+        let [summary_area, days_area] = vertical.areas(inner);
+
+        let balance = &self.balance;
+        Paragraph::new(vec![
+            Line::from(format!("Opening balance:  {}", format_minutes(balance.opening))),
+            Line::from(format!("Corrections:      {}", format_minutes(balance.corrections))),
+            Line::from(format!("Balance:          {}", format_minutes(balance.total())))
+                .style(Style::default().fg(balance_color(balance.total()))),
+        ])
+        .render(summary_area, buf);
+
+        let items = balance
+            .days
+            .iter()
+            .rev()
+            .map(|day| {
+                ListItem::new(format!(
+                    "{}  worked {:>6}  target {:>6}  {:>7}  = {:>7}",
+                    day.date.format("%a %Y-%m-%d"),
+                    format_minutes(day.worked),
+                    format_minutes(day.target),
+                    format_minutes(day.delta()),
+                    format_minutes(day.running),
+                ))
+                .style(Style::default().fg(balance_color(day.delta())))
+            })
+            .collect_vec();
+        Widget::render(List::new(items), days_area, buf);
+    }
+}
+
+fn balance_color(minutes: i64) -> Color {
+    if minutes < 0 {
+        Color::Red
+    } else {
+        Color::Green
+    }
+}
@@ -13,25 +13,57 @@ use crate::barchart::{draw_bar_with_group_labels, draw_legend, BarChartApp};
 #[derive(Clone)]
 pub struct BarChartTab {
     barchart_app: BarChartApp<'static>,
+    split_overnight: bool,
 }
 
 impl BarChartTab {
     pub fn new() -> Self {
         Self {
             barchart_app: BarChartApp::new(),
+            split_overnight: false,
         }
     }
+
+    /// A tab without data, for an app that never renders it.
+    pub fn empty() -> Self {
+        Self {
+            barchart_app: BarChartApp::empty(),
+            split_overnight: false,
+        }
+    }
+
+    /// Switch between charting overnight shifts on their start day and
+    /// splitting them at midnight.
+    pub fn toggle_split_overnight(&mut self) {
+        self.split_overnight = !self.split_overnight;
+        self.reload();
+    }
+
+    /// Read the stored days again after the history changed.
+    pub fn reload(&mut self) {
+        self.barchart_app = BarChartApp::with_split(self.split_overnight);
+    }
+}
+
+impl Default for BarChartTab {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Widget for BarChartTab {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        const LEGEND_HEIGHT: u16 = 6;
+        const LEGEND_HEIGHT: u16 = 7;
     
-        let barchart_app = BarChartApp::new();
         let barchart = draw_bar_with_group_labels(
-                &barchart_app,
+                &self.barchart_app,
                 false,
                 Block::bordered()
+                    .title(if self.split_overnight {
+                        " (o) shifts split at midnight "
+                    } else {
+                        " (o) shifts on their start day "
+                    })
                     .border_set(PROPORTIONAL_TALL)
                     .padding(Padding::horizontal(1))
                     .border_style(tailwind::INDIGO.c700)  
@@ -9,22 +9,23 @@ use ratatui::{
         palette::tailwind, Color,  Style, 
     }, 
     symbols::border::PROPORTIONAL_TALL, 
+    text::Line,
     widgets::{
         Block, List, ListItem, ListState, Padding, Paragraph, Widget, StatefulWidget
     }
 };
-use crate::read_json::{read_json, Worktime};
+use crate::calc_time::format_minutes;
+use crate::model::Worktime;
+use crate::read_json::read_json;
 use unicode_width::UnicodeWidthStr;
 use itertools::Itertools;
 
-const NORMAL_ROW_BG: Color = tailwind::SLATE.c950;
-const ALT_ROW_BG_COLOR: Color = tailwind::SLATE.c900;
 
 
-
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct EditHistoryTab {
     row_index: usize,
+    history: Vec<Worktime>,
 }
 
 
@@ -32,8 +33,31 @@ impl EditHistoryTab {
     pub fn new() -> Self {
         Self {
             row_index: 0,
+            history: Vec::new(),
         }
     }
+
+    /// A tab with the stored days loaded, oldest first.
+    pub fn load() -> Self {
+        let mut tab = Self::new();
+        tab.reload();
+        tab
+    }
+
+    /// Read the stored days again after the history changed.
+    pub fn reload(&mut self) {
+        self.history = read_json().unwrap_or_default();
+        self.history.sort_by(|a, b| a.date.cmp(&b.date));
+    }
+
+    pub fn len(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.history.is_empty()
+    }
+
     pub fn previous(&mut self) {
         self.row_index = self.row_index.saturating_sub(1);
     }
@@ -41,7 +65,7 @@ impl EditHistoryTab {
         self.row_index = self.row_index.saturating_add(1);
     }
 
-    fn block(self) -> Block<'static> {
+    fn block(&self) -> Block<'static> {
         Block::bordered()
             .border_set(PROPORTIONAL_TALL)
             .padding(Padding::horizontal(1))
@@ -49,32 +73,21 @@ impl EditHistoryTab {
     }
 }
 
-impl Widget for EditHistoryTab {
+impl Widget for &EditHistoryTab {
     fn render(self, area: Rect, buf: &mut Buffer) {
         self.block().render(area, buf);
         let vertical = Layout::vertical([Constraint::Length(5), Constraint::Min(0)]);
         let [overview, day] = vertical.areas(area);
-        render_overview(self.row_index, overview, buf);
-        render_day(self.row_index, day, buf);
+        render_overview(&self.history, self.row_index, overview, buf);
+        render_day(&self.history, self.row_index, day, buf);
     }
 }
-fn render_overview(row_index: usize, area: Rect, buf: &mut Buffer) {
+fn render_overview(worktime: &[Worktime], row_index: usize, area: Rect, buf: &mut Buffer) {
     let vertical = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]);
     let [tabs, _overview] = vertical.areas(area);
 
     let highlight_symbol = ">>";
 
-    let worktime = match read_json() {
-        Ok(worktime) => {
-            let mut worktime = worktime;
-            worktime.sort_by(|a, b| a.date.cmp(&b.date));
-            worktime
-        }
-        Err(_e) => {
-            vec![]
-        }
-    };
-
     let date_width = worktime
         .iter()
         .map(|e| e.date.width())
@@ -83,13 +96,13 @@ fn render_overview(row_index: usize, area: Rect, buf: &mut Buffer) {
 
     let starttime_width = worktime
         .iter()
-        .map(|e| e.starttime.width())
+        .map(|e| e.starttime.format("%H:%M").to_string().width())
         .max()
         .unwrap_or_default();
 
     let endtime_width = worktime
         .iter()
-        .map(|e| e.endtime.width())
+        .map(|e| e.endtime.format("%H:%M").to_string().width())
         .max()
         .unwrap_or_default();
 
@@ -97,8 +110,8 @@ fn render_overview(row_index: usize, area: Rect, buf: &mut Buffer) {
         .iter()
         .map(|e| {
             let date = format!("{:width$}", e.date, width = date_width).into();
-            let starttime = format!("{:width$}", e.starttime, width = starttime_width).into();
-            let endtime = format!("{:width$}", e.endtime, width = endtime_width).into();
+            let starttime = format!("{:width$}", e.starttime.format("%H:%M").to_string(), width = starttime_width).into();
+            let endtime = format!("{:width$}", e.endtime.format("%H:%M").to_string(), width = endtime_width).into();
             ListItem::new(vec![date, starttime, endtime])
             }
         )
@@ -118,30 +131,31 @@ fn render_overview(row_index: usize, area: Rect, buf: &mut Buffer) {
         
 }
 
-fn render_day(row_index: usize, area: Rect, buf: &mut Buffer) {
+fn render_day(worktime: &[Worktime], row_index: usize, area: Rect, buf: &mut Buffer) {
     let vertical = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]);
     let [_tabs, day] = vertical.areas(area);
 
-    let worktime = match read_json() {
-        Ok(worktime) => {
-            let mut worktime = worktime;
-            worktime.sort_by(|a, b| a.date.cmp(&b.date));
-            worktime
-        }
-        Err(_e) => {
-            vec![]
-        }
+    let Some(worktime) = worktime.get(row_index) else {
+        return;
     };
 
-    let worktime = worktime.get(row_index).unwrap();
-
     let date = format!("Date: {}", worktime.date);
-    let starttime = format!("Starttime: {}", worktime.starttime);
-    let endtime = format!("Endtime: {}", worktime.endtime);
+    let starttime = format!("Starttime: {}", worktime.starttime.format("%H:%M"));
+    let endtime = format!("Endtime: {}", worktime.endtime.format("%H:%M"));
+
+    let breaks = format!("Breaks: {} min", worktime.break_minutes());
 
     let date = Paragraph::new(date);
     let starttime = Paragraph::new(starttime);
     let endtime = Paragraph::new(endtime);
+    let breaks = Paragraph::new(breaks);
+    let projects = Paragraph::new(
+        worktime
+            .project_minutes()
+            .into_iter()
+            .map(|(project, minutes)| Line::from(format!("{}: {}", project, format_minutes(minutes))))
+            .collect_vec(),
+    );
     
         
     let meeting_name_width = worktime
@@ -154,14 +168,14 @@ fn render_day(row_index: usize, area: Rect, buf: &mut Buffer) {
     let meeting_start_time_width = worktime
         .meetings
         .iter()
-        .map(|e| e.meeting_start_time.width())
+        .map(|e| e.meeting_start_time.format("%H:%M").to_string().width())
         .max()
         .unwrap_or_default();
 
     let meeting_end_time_width = worktime
         .meetings
         .iter()
-        .map(|e| e.meeting_end_time.width())
+        .map(|e| e.meeting_end_time.format("%H:%M").to_string().width())
         .max()
         .unwrap_or_default();
 
@@ -170,8 +184,8 @@ fn render_day(row_index: usize, area: Rect, buf: &mut Buffer) {
         .iter()
         .map(|e| {
             let meeting_name = format!("{:width$}", e.meeting_name, width = meeting_name_width).into();
-            let meeting_start_time = format!("{:width$}", e.meeting_start_time, width = meeting_start_time_width).into();
-            let meeting_end_time = format!("{:width$}", e.meeting_end_time, width = meeting_end_time_width).into();
+            let meeting_start_time = format!("{:width$}", e.meeting_start_time.format("%H:%M").to_string(), width = meeting_start_time_width).into();
+            let meeting_end_time = format!("{:width$}", e.meeting_end_time.format("%H:%M").to_string(), width = meeting_end_time_width).into();
             ListItem::new(vec![meeting_name, meeting_start_time, meeting_end_time])
             }
         )
@@ -183,14 +197,14 @@ fn render_day(row_index: usize, area: Rect, buf: &mut Buffer) {
     let focus_time_start_width = worktime
         .focus_time
         .iter()
-        .map(|e| e.focus_time_start.width())
+        .map(|e| e.focus_time_start.format("%H:%M").to_string().width())
         .max()
         .unwrap_or_default();
 
     let focus_time_end_width = worktime
         .focus_time
         .iter()
-        .map(|e| e.focus_time_end.width())
+        .map(|e| e.focus_time_end.format("%H:%M").to_string().width())
         .max()
         .unwrap_or_default();
 
@@ -198,8 +212,8 @@ fn render_day(row_index: usize, area: Rect, buf: &mut Buffer) {
         .focus_time
         .iter()
         .map(|e| {
-            let focus_time_start = format!("{:width$}", e.focus_time_start, width = focus_time_start_width).into();
-            let focus_time_end = format!("{:width$}", e.focus_time_end, width = focus_time_end_width).into();
+            let focus_time_start = format!("{:width$}", e.focus_time_start.format("%H:%M").to_string(), width = focus_time_start_width).into();
+            let focus_time_end = format!("{:width$}", e.focus_time_end.format("%H:%M").to_string(), width = focus_time_end_width).into();
             ListItem::new(vec![focus_time_start, focus_time_end])
             }
         )
@@ -209,21 +223,21 @@ fn render_day(row_index: usize, area: Rect, buf: &mut Buffer) {
 
     let horizontal = Layout::horizontal([Constraint::Min(0); 3]);
     let [date_area, meetings_area, focus_time_area] = horizontal.areas(day);
-    let vertical = Layout::vertical([Constraint::Length(1); 3]);
-    let [date_area, starttime_area, endtime_area] = vertical.areas(date_area);
+    let vertical = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Min(0),
+    ]);
+    let [date_area, starttime_area, endtime_area, breaks_area, projects_area] = vertical.areas(date_area);
 
     date.render(date_area, buf);
     starttime.render(starttime_area, buf);
     endtime.render(endtime_area, buf);
+    breaks.render(breaks_area, buf);
+    projects.render(projects_area, buf);
     Widget::render(meetings_list, meetings_area, buf);
     Widget::render(focus_time_list, focus_time_area, buf);
 }     
 
-
-const fn alternate_colors_list(i: usize) -> Color {
-    if i % 2 == 0 {
-        NORMAL_ROW_BG
-    } else {
-        ALT_ROW_BG_COLOR
-    }
-}
\ No newline at end of file
@@ -22,6 +22,7 @@ use std::fs::File;
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct FocusTimeTab{
     row_index: usize,
+    paused: bool,
 }
 
 
@@ -29,8 +30,14 @@ impl FocusTimeTab {
     pub fn new() -> Self {
         Self {
             row_index: 0,
+            paused: false,
         }
     }
+
+    /// The tab showing whether the running session is paused.
+    pub fn paused(self, paused: bool) -> Self {
+        Self { paused, ..self }
+    }
     pub fn previous(&mut self) {
         self.row_index = self.row_index.saturating_sub(1);
     }
@@ -97,14 +104,15 @@ impl Widget for FocusTimeTab {
                     .border_set(PROPORTIONAL_TALL)
                     .padding(Padding::horizontal(1))
                     .border_style(tailwind::BLUE.c700)
-                    .on_blue()
+                    .bg(if self.paused { Color::Yellow } else { Color::Blue })
                     .border_type(BorderType::Rounded)
                 )
                 .render(inner_focus_chunks_top, buf);
     
-            Paragraph::new("Focus time: ".to_string() + focus_time.to_string().as_str() + " min")
+            let paused = if self.paused { " (paused)" } else { "" };
+            Paragraph::new("Focus time: ".to_string() + focus_time.to_string().as_str() + " min" + paused)
                 .centered()
-                .style(Style::default().fg(Color::Blue))
+                .style(Style::default().fg(if self.paused { Color::Yellow } else { Color::Blue }))
                 .block(Block::default().title("").borders(Borders::NONE))
                 .render(inner_focus_chunks_bottom,  buf);
 
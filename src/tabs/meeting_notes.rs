@@ -1,38 +1,474 @@
+use crate::model::{ActionItem, Timestamp, Worktime};
+use crate::read_json::read_json;
+use chrono::{Local, NaiveDate};
+use std::time::Instant;
+use crossterm::event::KeyCode;
 use ratatui::{
-    buffer::Buffer, 
-    layout::Rect, 
-    style::palette::tailwind, 
-     
-    symbols::border::PROPORTIONAL_TALL,   
-    widgets::{
-        Block, Padding, Paragraph, Widget
-    }
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{palette::tailwind, Color, Modifier, Style},
+    symbols::border::PROPORTIONAL_TALL,
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Padding, Paragraph, StatefulWidget, Widget, Wrap},
 };
 
+/// Where the notes of a meeting are kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoteTarget {
+    /// The meeting that is running now.
+    Running,
+    /// A finished meeting of today, by its index in the meeting list.
+    Today(usize),
+    /// A meeting of a stored day, by its index in that day's meetings.
+    Stored { date: String, index: usize },
+}
+
+/// A meeting as listed in the notes tab, borrowed from where it is kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoteEntry<'a> {
+    pub target: NoteTarget,
+    pub name: &'a str,
+    pub start: Timestamp,
+    pub notes: &'a str,
+    pub action_items: &'a [ActionItem],
+}
+
+/// An action item as listed across meetings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionItemEntry<'a> {
+    pub target: NoteTarget,
+    /// Position in the meeting's action items.
+    pub index: usize,
+    pub meeting: &'a str,
+    pub start: Timestamp,
+    pub item: &'a ActionItem,
+}
+
+/// What the notes tab lists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NotesView {
+    #[default]
+    Meetings,
+    /// The action items of all meetings.
+    ActionItems,
+}
 
+/// A plain multi-line text editor. The cursor column counts characters, not
+/// bytes.
 #[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteEditor {
+    lines: Vec<String>,
+    row: usize,
+    col: usize,
+}
+
+impl NoteEditor {
+    /// An editor on `text` with the cursor at its end.
+    pub fn new(text: &str) -> Self {
+        let mut lines: Vec<String> = text.split('\n').map(str::to_string).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        let row = lines.len() - 1;
+        let col = lines[row].chars().count();
+        Self { lines, row, col }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    fn byte_index(&self, col: usize) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices().nth(col).map_or(line.len(), |(i, _)| i)
+    }
+
+    fn line_len(&self) -> usize {
+        self.lines[self.row].chars().count()
+    }
+
+    /// Apply a key. Returns whether the text changed.
+    pub fn handle_key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Char(c) => {
+                let at = self.byte_index(self.col);
+                self.lines[self.row].insert(at, c);
+                self.col += 1;
+                true
+            }
+            KeyCode::Tab => {
+                let at = self.byte_index(self.col);
+                self.lines[self.row].insert_str(at, "    ");
+                self.col += 4;
+                true
+            }
+            KeyCode::Enter => {
+                let at = self.byte_index(self.col);
+                let rest = self.lines[self.row].split_off(at);
+                self.row += 1;
+                self.lines.insert(self.row, rest);
+                self.col = 0;
+                true
+            }
+            KeyCode::Backspace if self.col > 0 => {
+                let at = self.byte_index(self.col - 1);
+                self.lines[self.row].remove(at);
+                self.col -= 1;
+                true
+            }
+            KeyCode::Backspace if self.row > 0 => {
+                let line = self.lines.remove(self.row);
+                self.row -= 1;
+                self.col = self.line_len();
+                self.lines[self.row].push_str(&line);
+                true
+            }
+            KeyCode::Delete if self.col < self.line_len() => {
+                let at = self.byte_index(self.col);
+                self.lines[self.row].remove(at);
+                true
+            }
+            KeyCode::Delete if self.row + 1 < self.lines.len() => {
+                let next = self.lines.remove(self.row + 1);
+                self.lines[self.row].push_str(&next);
+                true
+            }
+            KeyCode::Left if self.col > 0 => {
+                self.col -= 1;
+                false
+            }
+            KeyCode::Left if self.row > 0 => {
+                self.row -= 1;
+                self.col = self.line_len();
+                false
+            }
+            KeyCode::Right if self.col < self.line_len() => {
+                self.col += 1;
+                false
+            }
+            KeyCode::Right if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.col = 0;
+                false
+            }
+            KeyCode::Up if self.row > 0 => {
+                self.row -= 1;
+                self.col = self.col.min(self.line_len());
+                false
+            }
+            KeyCode::Down if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.col = self.col.min(self.line_len());
+                false
+            }
+            KeyCode::Home => {
+                self.col = 0;
+                false
+            }
+            KeyCode::End => {
+                self.col = self.line_len();
+                false
+            }
+            _ => false,
+        }
+    }
+
+    // The visible lines, scrolled so that the cursor row is shown, with the
+    // character under the cursor reversed.
+    fn lines(&self, height: usize) -> Vec<Line<'static>> {
+        let first = self.row.saturating_sub(height.saturating_sub(1));
+        let cursor = Style::default().add_modifier(Modifier::REVERSED);
+        self.lines
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(height)
+            .map(|(row, line)| {
+                if row != self.row {
+                    return Line::from(line.clone());
+                }
+                let (before, rest) = line.split_at(self.byte_index(self.col));
+                let mut chars = rest.chars();
+                let under = chars.next().map_or(" ".to_string(), |c| c.to_string());
+                Line::from(vec![
+                    Span::raw(before.to_string()),
+                    Span::styled(under, cursor),
+                    Span::raw(chars.as_str().to_string()),
+                ])
+            })
+            .collect()
+    }
+}
+
+/// Notes of the running meeting and of past ones. The tab lists the meetings
+/// and shows the notes of the selected one; an editor is open while typing.
+#[derive(Debug, Clone, PartialEq)]
 pub struct MeetingNotesTab {
-    block: Block<'static>,
+    /// Stored days, newest first, for the notes of earlier meetings.
+    pub history: Vec<Worktime>,
+    pub selected: usize,
+    pub editor: Option<(NoteTarget, NoteEditor)>,
+    /// Whether the editor has changes that are not saved yet.
+    pub dirty: bool,
+    /// When the text last changed, to save once typing pauses.
+    pub edited_at: Instant,
+    pub view: NotesView,
+    pub item_selected: usize,
+    /// Filters the action items; without it only open ones are listed.
+    pub search: String,
 }
 
 impl MeetingNotesTab {
     pub fn new() -> Self {
         Self {
-            block: Block::default(),
+            history: Vec::new(),
+            selected: 0,
+            editor: None,
+            dirty: false,
+            edited_at: Instant::now(),
+            view: NotesView::Meetings,
+            item_selected: 0,
+            search: String::new(),
         }
     }
-    pub fn block(self) -> Block<'static> {
+
+    /// A tab with the stored days loaded.
+    pub fn load() -> Self {
+        let mut history = read_json().unwrap_or_default();
+        history.sort_by(|a, b| b.date.cmp(&a.date));
+        Self { history, ..Self::new() }
+    }
+
+    /// The meetings of the stored days, newest first.
+    pub fn stored_entries(&self) -> Vec<NoteEntry<'_>> {
+        self.history
+            .iter()
+            .flat_map(|day| {
+                day.meetings.iter().enumerate().rev().map(|(index, meeting)| NoteEntry {
+                    target: NoteTarget::Stored {
+                        date: day.date.clone(),
+                        index,
+                    },
+                    name: &meeting.meeting_name,
+                    start: meeting.meeting_start_time,
+                    notes: &meeting.notes,
+                    action_items: &meeting.action_items,
+                })
+            })
+            .collect()
+    }
+
+    /// The action items of `entries` as listed: the open ones, or with a
+    /// search all whose text, owner or meeting match it. Items due first come
+    /// first, those without a due date last.
+    pub fn action_items<'a>(&self, entries: &[NoteEntry<'a>]) -> Vec<ActionItemEntry<'a>> {
+        let search = self.search.trim().to_lowercase();
+        let mut items: Vec<ActionItemEntry> = entries
+            .iter()
+            .flat_map(|entry| {
+                entry.action_items.iter().enumerate().map(|(index, item)| ActionItemEntry {
+                    target: entry.target.clone(),
+                    index,
+                    meeting: entry.name,
+                    start: entry.start,
+                    item,
+                })
+            })
+            .filter(|entry| match search.as_str() {
+                "" => !entry.item.done,
+                search => entry.item.matches(search) || entry.meeting.to_lowercase().contains(search),
+            })
+            .collect();
+        items.sort_by_key(|entry| (entry.item.done, entry.item.due.is_none(), entry.item.due, entry.start));
+        items
+    }
+
+    /// Move down in the list shown, of `len` rows.
+    pub fn next(&mut self, len: usize) {
+        let selected = match self.view {
+            NotesView::Meetings => &mut self.selected,
+            NotesView::ActionItems => &mut self.item_selected,
+        };
+        if *selected + 1 < len {
+            *selected += 1;
+        }
+    }
+
+    pub fn previous(&mut self) {
+        let selected = match self.view {
+            NotesView::Meetings => &mut self.selected,
+            NotesView::ActionItems => &mut self.item_selected,
+        };
+        *selected = selected.saturating_sub(1);
+    }
+
+    /// Switch between the meetings and the action items.
+    pub fn toggle_view(&mut self) {
+        self.view = match self.view {
+            NotesView::Meetings => NotesView::ActionItems,
+            NotesView::ActionItems => NotesView::Meetings,
+        };
+    }
+
+    pub fn block(&self) -> Block<'static> {
         Block::bordered()
             .border_set(PROPORTIONAL_TALL)
             .padding(Padding::horizontal(1))
-            .border_style(tailwind::INDIGO.c700)
+            .border_style(tailwind::GREEN.c700)
+            .title_bottom(match self.view {
+                NotesView::Meetings => {
+                    " (↑/↓) meeting | (enter) edit notes | (a) action item | (o) open items | (W) write Markdown files "
+                }
+                NotesView::ActionItems => " (↑/↓) item | (d) done/undone | (/) search | (o) meetings ",
+            })
+    }
+
+    /// Render the list of `entries` and the notes of the selected one, or
+    /// the editor if it is open.
+    pub fn render_entries(&self, entries: &[NoteEntry], area: Rect, buf: &mut Buffer) {
+        let inner = self.block().inner(area);
+        self.block().render(area, buf);
+        if self.view == NotesView::ActionItems {
+            self.render_action_items(&self.action_items(entries), inner, buf);
+            return;
+        }
+        let [list_area, notes_area] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(inner);
+
+        if entries.is_empty() {
+            Paragraph::new("No meetings yet. Start one with (m); its notes are taken here.")
+                .wrap(Wrap { trim: true })
+                .render(inner, buf);
+            return;
+        }
+
+        let items: Vec<ListItem> = entries
+            .iter()
+            .map(|entry| {
+                let when = match entry.target {
+                    NoteTarget::Running => "now  ".to_string(),
+                    NoteTarget::Today(_) => entry.start.format("%H:%M").to_string(),
+                    NoteTarget::Stored { .. } => entry.start.format("%m-%d").to_string(),
+                };
+                let marker = if entry.notes.is_empty() { " " } else { "*" };
+                let style = match entry.target {
+                    NoteTarget::Running => Style::default().fg(Color::Yellow),
+                    _ => Style::default(),
+                };
+                ListItem::new(Line::from(format!("{} {} {}", when, marker, entry.name))).style(style)
+            })
+            .collect();
+        let mut state = ListState::default().with_selected(Some(self.selected.min(entries.len() - 1)));
+        StatefulWidget::render(
+            List::new(items)
+                .block(Block::default().title("Meetings"))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            list_area,
+            buf,
+            &mut state,
+        );
+
+        let notes_block = |title: String| {
+            Block::default()
+                .title(title)
+                .padding(Padding::left(1))
+                .borders(Borders::LEFT)
+        };
+        let shown = match &self.editor {
+            Some((target, _)) => entries.iter().find(|entry| &entry.target == target),
+            None => entries.get(self.selected.min(entries.len() - 1)),
+        };
+        let action_items = shown.map_or(&[][..], |entry| entry.action_items);
+        let [notes_area, items_area] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(if action_items.is_empty() { 0 } else { action_items.len() as u16 + 1 }),
+        ])
+        .areas(notes_area);
+        let today = Local::now().date_naive();
+        let lines: Vec<Line> = action_items.iter().map(|item| action_item_line(item, today)).collect();
+        Paragraph::new(lines)
+            .block(notes_block(String::from("Action items")))
+            .render(items_area, buf);
+
+        match &self.editor {
+            Some(_) if shown.is_none() => {}
+            Some((_, editor)) => {
+                let name = shown.map_or("", |entry| entry.name);
+                let title = format!("Notes: {}{}", name, if self.dirty { " (saving)" } else { "" });
+                let block = notes_block(title).style(Style::default().fg(Color::White));
+                let height = block.inner(notes_area).height as usize;
+                Paragraph::new(editor.lines(height)).block(block).render(notes_area, buf);
+            }
+            None => {
+                let Some(entry) = shown else {
+                    return;
+                };
+                let lines: Vec<Line> = if entry.notes.is_empty() {
+                    vec![Line::from("No notes. Press (enter) to write some.").style(Style::default().fg(Color::DarkGray))]
+                } else {
+                    entry.notes.lines().map(|line| Line::from(line.to_string())).collect()
+                };
+                Paragraph::new(lines)
+                    .wrap(Wrap { trim: false })
+                    .block(notes_block(format!("Notes: {}", entry.name)))
+                    .render(notes_area, buf);
+            }
+        }
+    }
+
+    fn render_action_items(&self, items: &[ActionItemEntry], area: Rect, buf: &mut Buffer) {
+        let title = if self.search.trim().is_empty() {
+            String::from("Open action items")
+        } else {
+            format!("Action items matching {:?}", self.search.trim())
+        };
+        let block = Block::default().title(title);
+        if items.is_empty() {
+            Paragraph::new("Nothing to follow up. Add action items to a meeting with (a).")
+                .wrap(Wrap { trim: true })
+                .block(block)
+                .render(area, buf);
+            return;
+        }
+        let today = Local::now().date_naive();
+        let rows: Vec<ListItem> = items
+            .iter()
+            .map(|entry| {
+                let mut line = action_item_line(entry.item, today);
+                line.spans.push(Span::styled(
+                    format!("  {} {}", entry.start.format("%Y-%m-%d"), entry.meeting),
+                    Style::default().fg(Color::DarkGray),
+                ));
+                ListItem::new(line)
+            })
+            .collect();
+        let mut state = ListState::default().with_selected(Some(self.item_selected.min(items.len() - 1)));
+        StatefulWidget::render(
+            List::new(rows)
+                .block(block)
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            area,
+            buf,
+            &mut state,
+        );
     }
 }
 
-impl Widget for MeetingNotesTab {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        Paragraph::new("Look! I'm different than others!")
-            .block(self.block())
-            .render(area, buf);    
-    }    
+// "[ ] text (@owner, due date)", red once overdue and grey when done
+fn action_item_line(item: &ActionItem, today: NaiveDate) -> Line<'static> {
+    let style = if item.is_overdue(today) {
+        Style::default().fg(Color::Red)
+    } else if item.done {
+        Style::default().fg(Color::DarkGray)
+    } else {
+        Style::default()
+    };
+    let mark = if item.done { "[x]" } else { "[ ]" };
+    Line::from(Span::styled(format!("{} {}", mark, item.describe()), style))
+}
+
+impl Default for MeetingNotesTab {
+    fn default() -> Self {
+        Self::new()
+    }
 }
@@ -0,0 +1,151 @@
+use ratatui::{
+    buffer::Buffer, 
+    layout::{
+        Constraint, 
+        Layout, 
+        Rect
+    }, 
+    style::{
+        palette::tailwind, Color, Style, 
+    }, 
+    symbols::border::PROPORTIONAL_TALL, 
+    text::Line,
+    widgets::{
+        Block, List, ListItem, Padding, Paragraph, Widget
+    }
+};
+use anyhow::Result;
+use chrono::Local;
+use itertools::Itertools;
+use std::path::PathBuf;
+use crate::calc_time::format_minutes;
+use crate::config::Config;
+use crate::read_json::read_json;
+use crate::report::{Period, Report, DEFAULT_REPORT_DIR};
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportTab {
+    config: Config,
+    report: Report,
+}
+
+impl ReportTab {
+    pub fn new() -> Self {
+        Self::with_config(&Config::load_default().unwrap_or_default())
+    }
+
+    /// The current week.
+    pub fn with_config(config: &Config) -> Self {
+        let period = Period::week_of(Local::now().date_naive());
+        Self {
+            config: config.clone(),
+            report: Self::build(config, period),
+        }
+    }
+
+    /// A tab without data, for an app that never renders it.
+    pub fn empty() -> Self {
+        Self {
+            config: Config::default(),
+            report: Report {
+                period: Period::week_of(Local::now().date_naive()),
+                days: Vec::new(),
+                projects: Vec::new(),
+            },
+        }
+    }
+
+    fn build(config: &Config, period: Period) -> Report {
+        let worktime = read_json().unwrap_or_default();
+        Report::build(&worktime, config, period, Local::now().date_naive())
+    }
+
+    fn show(&mut self, period: Period) {
+        self.report = Self::build(&self.config, period);
+    }
+
+    /// Build the shown period again after the history changed.
+    pub fn reload(&mut self) {
+        self.show(self.report.period);
+    }
+
+    pub fn previous_period(&mut self) {
+        self.show(self.report.period.previous());
+    }
+
+    pub fn next_period(&mut self) {
+        self.show(self.report.period.next());
+    }
+
+    /// Switch between the week and the month view.
+    pub fn toggle_period(&mut self) {
+        self.show(self.report.period.toggle());
+    }
+
+    /// Save the shown report as Markdown and HTML to `data/reports`.
+    pub fn write_files(&self) -> Result<Vec<PathBuf>> {
+        self.report.write_files(DEFAULT_REPORT_DIR)
+    }
+
+    fn block(&self) -> Block<'static> {
+        Block::bordered()
+            .border_set(PROPORTIONAL_TALL)
+            .padding(Padding::horizontal(1))
+            .border_style(tailwind::SKY.c700)
+            .title_bottom(" (↑/↓) period | (p) week/month | (W) save as Markdown and HTML ")
+    }
+}
+
+impl Default for ReportTab {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for &ReportTab {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = self.block();
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let summary = self.report.summary();
+        let projects = self.report.project_rows();
+        let project_lines = if projects.is_empty() { 0 } else { projects.len() + 2 };
+        let vertical = Layout::vertical([
+            Constraint::Length((summary.len() + 2 + project_lines) as u16),
+            Constraint::Min(0),
+        ]);
+        let [summary_area, days_area] = vertical.areas(inner);
+
+        let mut lines = vec![Line::from(self.report.period.title()).style(Style::default().fg(Color::Yellow)), Line::from("")];
+        lines.extend(summary.into_iter().map(|(label, value)| Line::from(format!("{:<18}{}", label, value))));
+        if !projects.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from("Projects").style(Style::default().fg(Color::Yellow)));
+        }
+        lines.extend(projects.into_iter().map(|[name, cost_center, time, share]| {
+            Line::from(format!("{:<18}{:>6}  {:>5}  {}", name, time, share, cost_center))
+        }));
+        Paragraph::new(lines).render(summary_area, buf);
+
+        let items = self
+            .report
+            .days
+            .iter()
+            .map(|day| {
+                ListItem::new(format!(
+                    "{}  worked {:>6}  target {:>6}  {:>7}  meetings {:>5}  focus {:>5}",
+                    day.date.format("%a %Y-%m-%d"),
+                    format_minutes(day.worked),
+                    format_minutes(day.target),
+                    format_minutes(day.overtime()),
+                    format_minutes(day.meetings),
+                    format_minutes(day.focus),
+                ))
+                .style(Style::default().fg(if day.overtime() < 0 { Color::Red } else { Color::Green }))
+            })
+            .collect_vec();
+        Widget::render(List::new(items), days_area, buf);
+    }
+}
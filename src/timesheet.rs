@@ -0,0 +1,178 @@
+use crate::calc_time::format_minutes;
+use crate::config::Config;
+use crate::model::Worktime;
+use crate::report::Period;
+use anyhow::Result;
+use chrono::{Datelike, Local, NaiveDate, Weekday};
+use printpdf::{BuiltinFont, Color, Line, Mm, PdfDocument, PdfLayerReference, Point, Rect, Rgb};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_TIMESHEET_DIR: &str = "data/timesheets";
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const ROW_HEIGHT: f32 = 6.0;
+// left edge of each column in mm
+const COLUMNS: [(&str, f32); 7] = [
+    ("Day", MARGIN),
+    ("Start", 62.0),
+    ("End", 82.0),
+    ("Breaks", 107.0),
+    ("Net", 127.0),
+    ("Target", 147.0),
+    ("+/-", 167.0),
+];
+
+pub fn default_timesheet_path(year: i32, month: u32) -> PathBuf {
+    PathBuf::from(DEFAULT_TIMESHEET_DIR).join(format!("timesheet_{}-{:02}.pdf", year, month))
+}
+
+/// One line of the sheet. A day without a record only has its target, a day
+/// still ahead not even that.
+struct SheetRow {
+    date: NaiveDate,
+    start: String,
+    end: String,
+    breaks: i64,
+    net: i64,
+    target: i64,
+    day_off: bool,
+}
+
+/// Render `month` of `year` as a one-page A4 timesheet to `path`. Weekends and
+/// the configured days off are shaded; days from today on are left blank
+/// unless they are stored already. Only the PDF base fonts are used, so
+/// nothing is embedded or downloaded.
+pub fn write_timesheet(worktime: &[Worktime], config: &Config, year: i32, month: u32, path: impl AsRef<Path>) -> Result<()> {
+    let period = Period::Month { year, month };
+    let today = Local::now().date_naive();
+    let mut recorded: HashMap<NaiveDate, Vec<&Worktime>> = HashMap::new();
+    for day in worktime {
+        if let Ok(date) = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d") {
+            recorded.entry(date).or_default().push(day);
+        }
+    }
+
+    let rows: Vec<SheetRow> = period
+        .first_day()
+        .iter_days()
+        .take_while(|date| *date <= period.last_day())
+        .map(|date| {
+            let days = recorded.get(&date).map(Vec::as_slice).unwrap_or_default();
+            let counted = date < today || !days.is_empty();
+            let start = days.iter().map(|d| d.starttime).min();
+            let end = days.iter().map(|d| d.endtime).max();
+            SheetRow {
+                date,
+                start: start.map(|t| t.format("%H:%M").to_string()).unwrap_or_default(),
+                end: end
+                    .map(|t| {
+                        let next_day = if t.date_naive() > date { " (+1)" } else { "" };
+                        format!("{}{}", t.format("%H:%M"), next_day)
+                    })
+                    .unwrap_or_default(),
+                breaks: days.iter().map(|d| d.break_minutes()).sum(),
+                net: days.iter().map(|d| d.worked_minutes(&config.breaks)).sum(),
+                target: if counted { config.schedule.target_minutes(date) } else { 0 },
+                day_off: config.schedule.days_off.contains(&date),
+            }
+        })
+        .collect();
+
+    let title = format!("Timesheet {}", period.title());
+    let (doc, page, layer) = PdfDocument::new(&title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Timesheet");
+    let layer = doc.get_page(page).get_layer(layer);
+    let regular = doc.add_builtin_font(BuiltinFont::Helvetica)?;
+    let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold)?;
+
+    let mut y = PAGE_HEIGHT - MARGIN - 5.0;
+    layer.use_text(&title, 16.0, Mm(MARGIN), Mm(y), &bold);
+    if !config.timesheet.employee.is_empty() {
+        y -= 7.0;
+        layer.use_text(format!("Employee: {}", config.timesheet.employee), 10.0, Mm(MARGIN), Mm(y), &regular);
+    }
+
+    y -= 12.0;
+    for (header, x) in COLUMNS {
+        layer.use_text(header, 10.0, Mm(x), Mm(y), &bold);
+    }
+    horizontal_line(&layer, y - 2.0);
+
+    for row in &rows {
+        y -= ROW_HEIGHT;
+        let weekend = matches!(row.date.weekday(), Weekday::Sat | Weekday::Sun);
+        if weekend || row.day_off {
+            shade(&layer, y);
+        }
+        let start = if row.day_off && row.start.is_empty() { "day off".to_string() } else { row.start.clone() };
+        let has_time = row.target > 0 || !row.start.is_empty();
+        let cells = [
+            row.date.format("%a %d.%m.%Y").to_string(),
+            start,
+            row.end.clone(),
+            if row.breaks > 0 { format_minutes(row.breaks) } else { String::new() },
+            if has_time { format_minutes(row.net) } else { String::new() },
+            if has_time { format_minutes(row.target) } else { String::new() },
+            if has_time { format_minutes(row.net - row.target) } else { String::new() },
+        ];
+        for ((_, x), cell) in COLUMNS.iter().zip(cells) {
+            if !cell.is_empty() {
+                layer.use_text(cell, 10.0, Mm(*x), Mm(y), &regular);
+            }
+        }
+    }
+
+    horizontal_line(&layer, y - 2.0);
+    y -= ROW_HEIGHT + 1.0;
+    let breaks: i64 = rows.iter().map(|r| r.breaks).sum();
+    let net: i64 = rows.iter().map(|r| r.net).sum();
+    let target: i64 = rows.iter().map(|r| r.target).sum();
+    let totals = [
+        (COLUMNS[0].1, "Total".to_string()),
+        (COLUMNS[3].1, format_minutes(breaks)),
+        (COLUMNS[4].1, format_minutes(net)),
+        (COLUMNS[5].1, format_minutes(target)),
+        (COLUMNS[6].1, format_minutes(net - target)),
+    ];
+    for (x, text) in totals {
+        layer.use_text(text, 10.0, Mm(x), Mm(y), &bold);
+    }
+
+    // signature lines at the bottom of the page
+    let signature_y = MARGIN + 10.0;
+    for (x, label) in [(MARGIN, "Date, signature employee"), (115.0, "Date, signature supervisor")] {
+        layer.add_line(Line {
+            points: vec![(point(x, signature_y), false), (point(x + 75.0, signature_y), false)],
+            is_closed: false,
+        });
+        layer.use_text(label, 8.0, Mm(x), Mm(signature_y - 4.0), &regular);
+    }
+
+    if let Some(parent) = path.as_ref().parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    doc.save(&mut BufWriter::new(File::create(path)?))?;
+    Ok(())
+}
+
+fn point(x: f32, y: f32) -> Point {
+    Point::new(Mm(x), Mm(y))
+}
+
+fn horizontal_line(layer: &PdfLayerReference, y: f32) {
+    layer.add_line(Line {
+        points: vec![(point(MARGIN, y), false), (point(PAGE_WIDTH - MARGIN, y), false)],
+        is_closed: false,
+    });
+}
+
+// grey background behind the row whose text baseline is at `y`
+fn shade(layer: &PdfLayerReference, y: f32) {
+    layer.set_fill_color(Color::Rgb(Rgb::new(0.9, 0.9, 0.9, None)));
+    layer.add_rect(Rect::new(Mm(MARGIN), Mm(y - 1.8), Mm(PAGE_WIDTH - MARGIN), Mm(y + ROW_HEIGHT - 1.8)));
+    layer.set_fill_color(Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None)));
+}
@@ -1,4 +1,4 @@
-use std::io::{self, Result};
+use std::io::Result;
 use std::{
   ops::{Deref, DerefMut},
   time::Duration,
@@ -196,7 +196,7 @@ impl Tui {
   }
 
   pub async fn next(&mut self) -> Result<Event> {
-    self.event_rx.recv().await.ok_or(std::io::Error::new(io::ErrorKind::Other, "No event received"))
+    self.event_rx.recv().await.ok_or(std::io::Error::other("No event received"))
   }
 }
 impl Deref for Tui {
@@ -3,6 +3,7 @@ use crate::app::{App, CurrentScreen, CurrentlyEditing};
 use chrono::Local;
 use ratatui::{
     layout::{
+        Alignment, 
         Constraint, 
         Direction, 
         Layout, 
@@ -24,6 +25,7 @@ use ratatui::{
         Clear, 
         List, 
         ListItem, 
+        Padding, 
         Paragraph, 
         Widget, 
         Wrap 
@@ -35,6 +37,7 @@ use ratatui::{
 use tui_big_text::{BigTextBuilder, PixelSize};
 
 use crate::barchart::{BarChartApp, draw_bar_with_group_labels};
+use crate::calc_time::{format_minutes, now};
 
 pub fn ui(f: &mut Frame<'_>, app: &mut App) {
     // Create the layout sections.
@@ -78,14 +81,62 @@ pub fn ui(f: &mut Frame<'_>, app: &mut App) {
         .borders(Borders::ALL)
         .style(Style::default().fg(Color::White));
 
-    let title = Paragraph::new(Text::styled(
-        "Time Tracker",
-        Style::default().fg(Color::Green),
-    ))
-    .block(title_block);
+    let mut title_spans = vec![Span::styled("Time Tracker", Style::default().fg(Color::Green))];
+    if app.project_running {
+        title_spans.push(Span::styled(
+            format!(" | Project: {} ({})", app.project_label(), format_minutes(i64::from(app.time_in_project))),
+            Style::default().fg(Color::Magenta),
+        ));
+    }
+    let title = Paragraph::new(Line::from(title_spans)).block(title_block);
 
     f.render_widget(title, chunks[0]);
 
+    // Overtime balance, right-aligned in the title bar
+    let balance_style = |minutes: i64| {
+        if minutes < 0 {
+            Style::default().fg(Color::Red)
+        } else {
+            Style::default().fg(Color::Green)
+        }
+    };
+    let mut balance_spans = Vec::new();
+    // Warn about a statutory break half an hour ahead
+    match app.break_due_in() {
+        Some(minutes) if minutes <= 0 => {
+            balance_spans.push(Span::styled("Break due! ", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)));
+            balance_spans.push(Span::styled("| ", Style::default().fg(Color::White)));
+        }
+        Some(minutes) if minutes <= 30 => {
+            balance_spans.push(Span::styled(format!("Break due in {} min ", minutes), Style::default().fg(Color::Yellow)));
+            balance_spans.push(Span::styled("| ", Style::default().fg(Color::White)));
+        }
+        _ => {}
+    }
+    // Action items of earlier meetings that are past their due date
+    match app.overdue_action_items() {
+        0 => {}
+        overdue => {
+            balance_spans.push(Span::styled(
+                format!("{} overdue action item{} ", overdue, if overdue == 1 { "" } else { "s" }),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ));
+            balance_spans.push(Span::styled("| ", Style::default().fg(Color::White)));
+        }
+    }
+    let (total, today) = (app.balance_total(now()), app.balance_today(now()));
+    balance_spans.extend([
+        Span::styled("Balance: ", Style::default().fg(Color::White)),
+        Span::styled(format_minutes(total), balance_style(total)),
+        Span::styled(" | Today: ", Style::default().fg(Color::White)),
+        Span::styled(format_minutes(today), balance_style(today)),
+    ]);
+    let balance = Paragraph::new(Line::from(balance_spans))
+    .alignment(Alignment::Right)
+    .block(Block::default().borders(Borders::ALL).padding(Padding::horizontal(1)));
+
+    f.render_widget(balance, chunks[0]);
+
     // List of Starttime and Endtime
 
     let mut starttime_list_items = Vec::<ListItem>::new();
@@ -93,18 +144,32 @@ pub fn ui(f: &mut Frame<'_>, app: &mut App) {
 
     for key in app.starttime_pairs.keys() {
         starttime_list_items.push(ListItem::new(Line::from(Span::styled(
-            format!("{: <25} : {}", key, app.starttime_pairs.get(key).unwrap()),
+            format!("{: <25} : {}", key, app.starttime_pairs[key].format("%H:%M")),
             Style::default().fg(Color::Yellow),
         ))));
     }
 
     for key in app.endtime_pairs.keys() {
         endtime_list_items.push(ListItem::new(Line::from(Span::styled(
-            format!("{: <25} : {}", key, app.endtime_pairs.get(key).unwrap()),
+            format!("{: <25} : {}", key, app.endtime_pairs[key].format("%H:%M")),
             Style::default().fg(Color::Yellow),
         ))));
     }
 
+    starttime_list_items.push(ListItem::new(Line::from(Span::styled(
+        format!("{: <25} : {}", "Target end", app.predicted_endtime().format("%H:%M")),
+        Style::default().fg(Color::Cyan),
+    ))));
+
+    endtime_list_items.push(ListItem::new(Line::from(Span::styled(
+        format!(
+            "{: <25} : {}",
+            "Time left today",
+            format_minutes(app.target_minutes() - app.current_worktime as i64)
+        ),
+        Style::default().fg(Color::Cyan),
+    ))));
+
     let starttime_list = List::new(starttime_list_items).block(
         Block::default()
             .title("Worktime")
@@ -125,12 +190,19 @@ pub fn ui(f: &mut Frame<'_>, app: &mut App) {
     let mut meeting_list_items = Vec::<ListItem>::new();
 
     for meeting in &app.meeting_list {
+        let mut name = match &meeting.project {
+            Some(project) => format!("{} [{}]", meeting.meeting_name, project),
+            None => meeting.meeting_name.clone(),
+        };
+        if !meeting.billable {
+            name.push_str(" (non-billable)");
+        }
         meeting_list_items.push(ListItem::new(Line::from(Span::styled(
             format!(
                 "{: <25} : {} - {} ({} min)",
-                meeting.meeting_name,
-                meeting.meeting_start_time,
-                meeting.meeting_end_time,
+                name,
+                meeting.meeting_start_time.format("%H:%M"),
+                meeting.meeting_end_time.format("%H:%M"),
                 meeting.time_in_meeting
             ),
             Style::default().fg(Color::Yellow),
@@ -140,16 +212,65 @@ pub fn ui(f: &mut Frame<'_>, app: &mut App) {
     if app.meeting_running {
         meeting_list_items.push(ListItem::new(Line::from(Span::styled(
             format!(
-                "{: <25} : {} - Ongoing Meeting ({} min)",
-                app.meeting_name, app.meeting_start_time, app.time_in_meetings
+                "{: <25} : {} - {} ({} min)",
+                if app.meeting_billable {
+                    app.meeting_name.clone()
+                } else {
+                    format!("{} (non-billable)", app.meeting_name)
+                },
+                app.meeting_start_time.format("%H:%M"),
+                if app.meeting_paused { "Paused Meeting" } else { "Ongoing Meeting" },
+                app.time_in_meetings
             ),
             Style::default().fg(Color::Yellow),
         ))));
     };
 
+    for pause in &app.break_list {
+        meeting_list_items.push(ListItem::new(Line::from(Span::styled(
+            format!(
+                "{: <25} : {} - {} ({} min)",
+                "Break",
+                pause.break_start_time.format("%H:%M"),
+                pause.break_end_time.format("%H:%M"),
+                pause.time_in_break
+            ),
+            Style::default().fg(Color::Cyan),
+        ))));
+    }
+
+    if app.break_running {
+        meeting_list_items.push(ListItem::new(Line::from(Span::styled(
+            format!(
+                "{: <25} : {} - Ongoing Break ({} min)",
+                "Break",
+                app.break_start_time.format("%H:%M"),
+                app.time_in_break
+            ),
+            Style::default().fg(Color::Cyan),
+        ))));
+    };
+
+    for session in &app.project_list {
+        let label = match &session.task {
+            Some(task) => format!("{} / {}", session.project, task),
+            None => session.project.clone(),
+        };
+        meeting_list_items.push(ListItem::new(Line::from(Span::styled(
+            format!(
+                "{: <25} : {} - {} ({} min)",
+                label,
+                session.project_start_time.format("%H:%M"),
+                session.project_end_time.format("%H:%M"),
+                session.time_in_project
+            ),
+            Style::default().fg(Color::Magenta),
+        ))));
+    }
+
     let meeting_list = List::new(meeting_list_items).block(
         Block::default()
-            .title("Meetings")
+            .title("Meetings, Breaks and Projects")
             .borders(Borders::ALL)
             .style(Style::default().fg(Color::White)),
     );
@@ -169,7 +290,8 @@ pub fn ui(f: &mut Frame<'_>, app: &mut App) {
     let barchart_app_today = BarChartApp::new_current(
                 app.current_worktime, 
                 app.total_time_in_meetings as u64, 
-                app.focus_time_total as u64, 
+                app.focus_time_total, 
+                app.target_minutes().max(0) as u64,
                 current_date);
     let barchart_today = draw_bar_with_group_labels(&barchart_app_today, true, today_block);
 
@@ -214,6 +336,26 @@ pub fn ui(f: &mut Frame<'_>, app: &mut App) {
                 Span::styled("Normal Mode", Style::default().fg(Color::DarkGray))
             }
 
+            CurrentScreen::EditingProject => {
+                Span::styled("Normal Mode", Style::default().fg(Color::DarkGray))
+            }
+
+            CurrentScreen::EditingNotes => {
+                Span::styled("Normal Mode", Style::default().fg(Color::DarkGray))
+            }
+
+            CurrentScreen::AddingActionItem => {
+                Span::styled("Normal Mode", Style::default().fg(Color::DarkGray))
+            }
+
+            CurrentScreen::SearchingActionItems => {
+                Span::styled("Normal Mode", Style::default().fg(Color::DarkGray))
+            }
+
+            CurrentScreen::ExportingCsv => {
+                Span::styled("Normal Mode", Style::default().fg(Color::DarkGray))
+            }
+
             CurrentScreen::Exiting => Span::styled("Exiting", Style::default().fg(Color::LightRed)),
         }
         .to_owned(),
@@ -232,7 +374,24 @@ pub fn ui(f: &mut Frame<'_>, app: &mut App) {
                     CurrentlyEditing::MeetingName => {
                         Span::styled("Editing Meeting Name", Style::default().fg(Color::Green))
                     }
+                    CurrentlyEditing::Project => {
+                        Span::styled("Editing Project", Style::default().fg(Color::Green))
+                    }
+                    CurrentlyEditing::Notes => {
+                        Span::styled("Editing Meeting Notes", Style::default().fg(Color::Green))
+                    }
+                    CurrentlyEditing::ActionItem => {
+                        Span::styled("Editing Action Item", Style::default().fg(Color::Green))
+                    }
+                    CurrentlyEditing::ActionItemSearch => {
+                        Span::styled("Searching Action Items", Style::default().fg(Color::Green))
+                    }
+                    CurrentlyEditing::ExportRange => {
+                        Span::styled("Editing Export Range", Style::default().fg(Color::Green))
+                    }
                 }
+            } else if let Some(message) = &app.export_message {
+                Span::styled(message.clone(), Style::default().fg(Color::Yellow))
             } else {
                 Span::styled("Not Editing Anything", Style::default().fg(Color::DarkGray))
             }
@@ -245,7 +404,7 @@ pub fn ui(f: &mut Frame<'_>, app: &mut App) {
     let current_keys_hint = {
         match app.current_screen {
             CurrentScreen::Main => Span::styled(
-                "Press (q) to quit | (s) to edit Starttime | (e) to edit Endtime | (m) start Meeting | (M) stop Meeting",
+                "Press (q) to quit | (s) to edit Starttime | (e) to edit Endtime | (m) start Meeting | (M) stop Meeting | (P) pause/resume | (b) start Break | (B) stop Break | (t) start Project | (T) stop Project | ($) billable on/off | (x) export CSV | (i) import calendar",
                 Style::default().fg(Color::Red),
             ),
             CurrentScreen::EditingStarttime => Span::styled(
@@ -257,11 +416,31 @@ pub fn ui(f: &mut Frame<'_>, app: &mut App) {
                 Style::default().fg(Color::Red),
             ),
             CurrentScreen::EditingMeetingName => Span::styled(
-                "(ESC) to cancel | (enter) to complete",
+                "(ESC) to cancel | (enter) to complete | (1-9) pick from the calendar",
+                Style::default().fg(Color::Red),
+            ),
+            CurrentScreen::EditingProject => Span::styled(
+                "(ESC) to cancel | (enter) to book PROJECT or PROJECT/TASK | (1-9) pick a configured project",
+                Style::default().fg(Color::Red),
+            ),
+            CurrentScreen::EditingNotes => Span::styled(
+                "(ESC) to save and close | (arrows) move | notes are saved while typing",
+                Style::default().fg(Color::Red),
+            ),
+            CurrentScreen::AddingActionItem => Span::styled(
+                "(ESC) to cancel | (enter) to add TEXT, optionally with @OWNER and due:YYYY-MM-DD",
+                Style::default().fg(Color::Red),
+            ),
+            CurrentScreen::SearchingActionItems => Span::styled(
+                "(ESC) to clear the search | (enter) to keep it | matches text, owner and meeting, done items included",
+                Style::default().fg(Color::Red),
+            ),
+            CurrentScreen::ExportingCsv => Span::styled(
+                "(ESC) to cancel | (enter) to export FROM TO as CSV",
                 Style::default().fg(Color::Red),
             ),
             CurrentScreen::Exiting => Span::styled(
-                "Press (q) to quit | (s) to edit Starttime | (e) to edit Endtime | (m) start/stop Meeting | (M) stop Meeting",
+                "Press (q) to quit | (s) to edit Starttime | (e) to edit Endtime | (m) start/stop Meeting | (M) stop Meeting | (b) start Break | (B) stop Break | (x) export CSV",
                 Style::default().fg(Color::Red),
             ),
         }
@@ -308,6 +487,14 @@ pub fn ui(f: &mut Frame<'_>, app: &mut App) {
 
         let mut meeting_block = Block::default().title("Meeting Name").borders(Borders::ALL).style(Style::default().fg(Color::White));
 
+        let mut project_block = Block::default().title("Project[/Task]").borders(Borders::ALL).style(Style::default().fg(Color::White));
+
+        let mut action_item_block = Block::default().title("Action item").borders(Borders::ALL).style(Style::default().fg(Color::White));
+
+        let mut search_block = Block::default().title("Search action items").borders(Borders::ALL).style(Style::default().fg(Color::White));
+
+        let mut export_block = Block::default().title("CSV export (from to)").borders(Borders::ALL).style(Style::default().fg(Color::White));
+
         let active_style = Style::default().bg(Color::LightYellow).fg(Color::Black);
 
         match editing {
@@ -328,7 +515,86 @@ pub fn ui(f: &mut Frame<'_>, app: &mut App) {
                 let value_text =
                     Paragraph::new(app.meeting_name_input.clone()).block(meeting_block);
                 f.render_widget(value_text, area);
+
+                // calendar suggestions right below the input
+                if !app.meeting_suggestions.is_empty() {
+                    let height = (app.meeting_suggestions.len() as u16 + 2)
+                        .min(f.size().bottom().saturating_sub(area.bottom()));
+                    let list_area = Rect::new(area.x, area.bottom(), area.width, height);
+                    let lines: Vec<Line> = app
+                        .meeting_suggestions
+                        .iter()
+                        .enumerate()
+                        .map(|(i, event)| Line::from(format!("({}) {} {}", i + 1, event.time_range(), event.summary)))
+                        .collect();
+                    let suggestions = Paragraph::new(lines).block(
+                        Block::default()
+                            .title("From the calendar")
+                            .borders(Borders::ALL)
+                            .style(Style::default().fg(Color::White)),
+                    );
+                    f.render_widget(Clear, list_area);
+                    f.render_widget(suggestions, list_area);
+                }
+            }
+
+            CurrentlyEditing::Project => {
+                project_block = project_block.style(active_style);
+                let value_text = Paragraph::new(app.project_input.clone()).block(project_block);
+                f.render_widget(value_text, area);
+
+                // the configured projects right below the input
+                if !app.config.projects.is_empty() {
+                    let shown = app.config.projects.len().min(9);
+                    let height = (shown as u16 + 2).min(f.size().bottom().saturating_sub(area.bottom()));
+                    let list_area = Rect::new(area.x, area.bottom(), area.width, height);
+                    let lines: Vec<Line> = app
+                        .config
+                        .projects
+                        .iter()
+                        .take(shown)
+                        .enumerate()
+                        .map(|(i, project)| {
+                            let tasks = if project.tasks.is_empty() {
+                                String::new()
+                            } else {
+                                format!(" ({})", project.tasks.join(", "))
+                            };
+                            Line::from(format!("({}) {}{}", i + 1, project.name, tasks))
+                        })
+                        .collect();
+                    let projects = Paragraph::new(lines).block(
+                        Block::default()
+                            .title("Projects")
+                            .borders(Borders::ALL)
+                            .style(Style::default().fg(Color::White)),
+                    );
+                    f.render_widget(Clear, list_area);
+                    f.render_widget(projects, list_area);
+                }
+            }
+
+            CurrentlyEditing::ExportRange => {
+                export_block = export_block.style(active_style);
+                let value_text =
+                    Paragraph::new(app.export_range_input.clone()).block(export_block);
+                f.render_widget(value_text, area);
             }
+
+            CurrentlyEditing::ActionItem => {
+                action_item_block = action_item_block.style(active_style);
+                let value_text = Paragraph::new(app.action_item_input.clone()).block(action_item_block);
+                f.render_widget(value_text, area);
+            }
+
+            CurrentlyEditing::ActionItemSearch => {
+                search_block = search_block.style(active_style);
+                let value_text = Paragraph::new(app.meeting_notes_tab.search.clone()).block(search_block);
+                f.render_widget(value_text, area);
+            }
+
+            // edited in place in the notes tab
+            CurrentlyEditing::Notes => {}
         };
     }
 